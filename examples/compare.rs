@@ -0,0 +1,54 @@
+//! Compares the quality metrics of the crate's generators against baselines.
+//!
+//! ```text
+//! cargo run --release --example compare -- --dims 2,3,4 --npoints 100,200,400 \
+//!     --methods sphere_n,cylind_n,random > results.csv
+//! ```
+//!
+//! Every option is optional; the defaults of `ExperimentConfig` are used otherwise.
+
+use sphere_n_rs::experiment::{run_experiment, write_csv, ExperimentConfig, Method};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
+
+fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, String>
+where
+    T::Err: ToString,
+{
+    value
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|e: T::Err| e.to_string()))
+        .collect()
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut config = ExperimentConfig::default();
+    let mut output: Option<String> = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", flag))?;
+        match flag.as_str() {
+            "--dims" => config.dims = parse_list(&value)?,
+            "--npoints" => config.npoints = parse_list(&value)?,
+            "--methods" => config.methods = parse_list::<Method>(&value)?,
+            "--ncaps" => config.ncaps = value.parse()?,
+            "--nprobes" => config.nprobes = value.parse()?,
+            "--seed" => config.seed = value.parse()?,
+            "--out" => output = Some(value),
+            _ => return Err(format!("unknown option `{}`", flag).into()),
+        }
+    }
+
+    let rows = run_experiment(&config);
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    write_csv(&rows, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+"""Tests of the Python bindings, against the reference values of the Rust tests in lib.rs."""
+
+import numpy as np
+import pytest
+
+from sphere_n_rs import CylindN, SphereN
+
+
+def test_sphere_n():
+    sgen = SphereN(5)
+    sgen.reseed(0)
+    res = sgen.pop()
+    expected = [
+        0.361367435929826,
+        0.18966026119111232,
+        -0.5807571031092044,
+        0.531081205910399,
+        0.4171022266758069,
+        0.20035526944272675,
+        6.123233995736766e-17,
+    ]
+    assert res == pytest.approx(expected, abs=1e-12)
+
+
+def test_sphere_n_3d():
+    sgen = SphereN(3, [2, 3, 5, 7])
+    res = sgen.pop()
+    expected = [
+        0.4809684718990214,
+        0.6031153874276115,
+        -0.5785601510223212,
+        0.2649326520763179,
+    ]
+    assert len(res) == 5
+    assert res[:4] == pytest.approx(expected, abs=1e-12)
+
+
+def test_cylind_n():
+    cgen = CylindN(5)
+    cgen.reseed(0)
+    for _ in range(10):
+        cgen.pop()
+    res = cgen.pop()
+    expected = [
+        0.6284456558612375,
+        0.032662755534715766,
+        0.11758502909407893,
+        -0.31367724756166177,
+        0.3180323054739925,
+        0.625,
+    ]
+    assert res == pytest.approx(expected, abs=1e-12)
+
+
+def test_cylind_n_2d():
+    cgen = CylindN(2)
+    assert cgen.pop() == pytest.approx([-0.5, 0.8660254037844387, 0.0], abs=1e-12)
+
+
+def test_pop_batch():
+    sgen = SphereN(4)
+    pts = sgen.pop_batch(100)
+    assert isinstance(pts, np.ndarray)
+    assert pts.shape == (100, 6)
+    assert pts.dtype == np.float64
+    assert np.allclose(np.linalg.norm(pts, axis=1), 1.0)
+    sgen.reseed(0)
+    assert sgen.pop() == pytest.approx(pts[0].tolist(), abs=0.0)
+
+
+def test_reseed_and_point_at():
+    cgen = CylindN(4)
+    cgen.reseed(20)
+    assert cgen.index == 20
+    first = cgen.pop()
+    assert cgen.index == 21
+    assert cgen.point_at(20) == first
+    # point_at does not move the generator
+    assert cgen.index == 21
+    cgen.reseed(20)
+    assert cgen.pop() == first
+
+
+def test_invalid_arguments():
+    with pytest.raises(ValueError):
+        SphereN(2)
+    with pytest.raises(ValueError):
+        SphereN(4, [2, 3, 5])
+    with pytest.raises(ValueError):
+        CylindN(3, [2, 4, 5])
+    assert repr(CylindN(2)) == "CylindN(n=2, base=[2, 3], index=0)"
@@ -0,0 +1,414 @@
+use crate::cylind_n::{CylindGen, CylindN};
+use crate::rng::{random_rotation, SplitMix64};
+use crate::sphere_n::{sphere_gen, Inversion, SphereN, GRID_SIZE};
+use crate::state::GenState;
+use lds_rs::lds::{Circle, PRIME_TABLE};
+use ndarray::{Array1, Array2};
+
+/// Builds a generator of points on `S^n` with the recursive sphere method.
+///
+/// All options are validated once, in `build`, which returns an error instead of panicking.
+/// The defaults reproduce `sphere_gen(n, &PRIME_TABLE)` exactly: the first `n` primes as
+/// bases, `GRID_SIZE` interpolation nodes, linear interpolation of the CDF tables, no
+/// scrambling and a start at index 0.
+///
+/// * `grid_size` sets the number of nodes of the interpolation tables.
+/// * `inversion` selects how the polar-angle CDF is inverted; `Inversion::Newton` sets the
+///   precision of the angles.
+/// * `scramble` applies a random rotation, drawn from the seed, to every point, for
+///   randomized QMC.
+/// * `start_index` skips the first points of the sequence, as `reseed` does.
+///
+/// The table options only apply to `SphereN`, that is for `n >= 4`; setting them for a
+/// smaller `n` is an error rather than being silently ignored.
+///
+/// Like `sphere_gen`, the builder takes the dimension `n` of the sphere `S^n`, which is one
+/// more than the `n` of `SphereN::new`: `SphereN::new(n, base)` generates `S^(n+1)`. Use
+/// `for_sphere_n` to start from the `n` of `SphereN::new`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::builder::SphereBuilder;
+/// use sphere_n_rs::sphere_n::Inversion;
+///
+/// let mut sgen = SphereBuilder::new(5)
+///     .inversion(Inversion::Newton { tolerance: 1e-12 })
+///     .start_index(100)
+///     .build()
+///     .unwrap();
+/// assert_eq!(sgen.pop_vec().len(), 6);
+///
+/// assert!(SphereBuilder::new(5).base(&[2, 3, 4, 5, 7]).build().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphereBuilder {
+    n: usize,
+    base: Vec<usize>,
+    grid_size: usize,
+    inversion: Inversion,
+    scramble: Option<u64>,
+    start_index: usize,
+}
+
+impl SphereBuilder {
+    /// Starts a builder for points on `S^n`, that is in `R^(n+1)`, with the default options.
+    ///
+    /// `n` is the dimension of the sphere, not the `n` of `SphereN::new`.
+    pub fn new(n: usize) -> Self {
+        SphereBuilder {
+            n,
+            base: PRIME_TABLE.iter().take(n).copied().collect(),
+            grid_size: GRID_SIZE,
+            inversion: Inversion::default(),
+            scramble: None,
+            start_index: 0,
+        }
+    }
+
+    /// Starts a builder for the generator `SphereN::new(n, ..)`, that is for points on
+    /// `S^(n+1)`, with the default options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sphere_n_rs::builder::SphereBuilder;
+    /// use sphere_n_rs::SphereN;
+    ///
+    /// let mut sgen = SphereBuilder::for_sphere_n(4).build_sphere_n().unwrap();
+    /// assert_eq!(sgen.pop_vec(), SphereN::new(4, &[2, 3, 5, 7, 11]).pop_vec());
+    /// ```
+    pub fn for_sphere_n(n: usize) -> Self {
+        Self::new(n + 1)
+    }
+
+    /// Sets the bases of the underlying sequences; the first `n` are used.
+    pub fn base(mut self, base: &[usize]) -> Self {
+        self.base = base.to_vec();
+        self
+    }
+
+    /// Sets the number of nodes of the interpolation tables, at every level of the recursion.
+    pub fn grid_size(mut self, grid_size: usize) -> Self {
+        self.grid_size = grid_size;
+        self
+    }
+
+    /// Sets the inversion method of the polar-angle CDF.
+    pub fn inversion(mut self, inversion: Inversion) -> Self {
+        self.inversion = inversion;
+        self
+    }
+
+    /// Rotates every point by a random rotation drawn from `seed`.
+    pub fn scramble(mut self, seed: u64) -> Self {
+        self.scramble = Some(seed);
+        self
+    }
+
+    /// Starts the sequence at `index` instead of 0.
+    pub fn start_index(mut self, index: usize) -> Self {
+        self.start_index = index;
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.n == 0 {
+            return Err("the sphere dimension must be at least 1".to_string());
+        }
+        validate_bases(&self.base, self.n)?;
+        if self.grid_size < 2 {
+            return Err("the grid needs at least 2 nodes".to_string());
+        }
+        self.inversion.validate()?;
+        let default_tables = self.grid_size == GRID_SIZE && self.inversion == Inversion::default();
+        if self.n < 4 && !default_tables {
+            return Err(format!(
+                "the grid size and inversion only apply to S^n with n >= 4, found n = {}",
+                self.n
+            ));
+        }
+        Ok(())
+    }
+
+    /// Validates the options and builds the generator.
+    pub fn build(&self) -> Result<Box<dyn CylindGen>, String> {
+        self.validate()?;
+        let mut res: Box<dyn CylindGen> = if self.n < 4 {
+            sphere_gen(self.n, &self.base)
+        } else {
+            Box::new(self.make_sphere_n())
+        };
+        res.reseed(self.start_index);
+        Ok(match self.scramble {
+            Some(seed) => scrambled(res, seed, self.n + 1),
+            None => res,
+        })
+    }
+
+    /// Validates the options and builds a `SphereN`, which needs `n >= 4` and no scrambling.
+    ///
+    /// The result is `SphereN::new(n - 1, ..)`, since it generates `S^n`; start the builder
+    /// with `for_sphere_n` to give the `n` of `SphereN::new` instead.
+    pub fn build_sphere_n(&self) -> Result<SphereN, String> {
+        self.validate()?;
+        if self.n < 4 {
+            return Err(format!("SphereN needs n >= 4, found n = {}", self.n));
+        }
+        if self.scramble.is_some() {
+            return Err("a scrambled generator is not a SphereN".to_string());
+        }
+        let mut res = self.make_sphere_n();
+        res.reseed(self.start_index);
+        Ok(res)
+    }
+
+    fn make_sphere_n(&self) -> SphereN {
+        SphereN::with_options(self.n - 1, &self.base, self.grid_size, self.inversion)
+    }
+}
+
+/// Builds a generator of points on `S^n` with the cylindrical coordinate method.
+///
+/// The defaults reproduce `CylindN::new(n, &PRIME_TABLE)` exactly; `scramble` and
+/// `start_index` behave as for `SphereBuilder`. For `n = 1` the generator is a `Circle`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::builder::CylindBuilder;
+/// use sphere_n_rs::{CylindGen, CylindN};
+///
+/// let mut cgen = CylindBuilder::new(3).start_index(5).build_cylind_n().unwrap();
+/// let mut expected = CylindN::new(3, &[2, 3, 5]);
+/// expected.reseed(5);
+/// assert_eq!(cgen.pop_vec(), expected.pop_vec());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CylindBuilder {
+    n: usize,
+    base: Vec<usize>,
+    scramble: Option<u64>,
+    start_index: usize,
+}
+
+impl CylindBuilder {
+    /// Starts a builder for points on `S^n`, that is in `R^(n+1)`, with the default options.
+    pub fn new(n: usize) -> Self {
+        CylindBuilder {
+            n,
+            base: PRIME_TABLE.iter().take(n).copied().collect(),
+            scramble: None,
+            start_index: 0,
+        }
+    }
+
+    /// Sets the bases of the underlying sequences; the first `n` are used.
+    pub fn base(mut self, base: &[usize]) -> Self {
+        self.base = base.to_vec();
+        self
+    }
+
+    /// Rotates every point by a random rotation drawn from `seed`.
+    pub fn scramble(mut self, seed: u64) -> Self {
+        self.scramble = Some(seed);
+        self
+    }
+
+    /// Starts the sequence at `index` instead of 0.
+    pub fn start_index(mut self, index: usize) -> Self {
+        self.start_index = index;
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.n == 0 {
+            return Err("the sphere dimension must be at least 1".to_string());
+        }
+        validate_bases(&self.base, self.n)
+    }
+
+    /// Validates the options and builds the generator.
+    pub fn build(&self) -> Result<Box<dyn CylindGen>, String> {
+        self.validate()?;
+        let mut res: Box<dyn CylindGen> = if self.n == 1 {
+            Box::new(Circle::new(self.base[0]))
+        } else {
+            Box::new(CylindN::new(self.n, &self.base))
+        };
+        res.reseed(self.start_index);
+        Ok(match self.scramble {
+            Some(seed) => scrambled(res, seed, self.n + 1),
+            None => res,
+        })
+    }
+
+    /// Validates the options and builds a `CylindN`, which needs `n >= 2` and no scrambling.
+    pub fn build_cylind_n(&self) -> Result<CylindN, String> {
+        self.validate()?;
+        if self.n < 2 {
+            return Err(format!("CylindN needs n >= 2, found n = {}", self.n));
+        }
+        if self.scramble.is_some() {
+            return Err("a scrambled generator is not a CylindN".to_string());
+        }
+        let mut res = CylindN::new(self.n, &self.base);
+        res.reseed(self.start_index);
+        Ok(res)
+    }
+}
+
+/// Checks that there are at least `count` bases, that they are at least 2 and that the
+/// first `count` are pairwise coprime, as the van der Corput sequences need.
+fn validate_bases(base: &[usize], count: usize) -> Result<(), String> {
+    if base.len() < count {
+        return Err(format!(
+            "expected at least {} bases, found {}",
+            count,
+            base.len()
+        ));
+    }
+    let base = &base[..count];
+    if base.iter().any(|&b| b < 2) {
+        return Err("the bases must be at least 2".to_string());
+    }
+    for (i, &a) in base.iter().enumerate() {
+        if let Some(&b) = base[i + 1..].iter().find(|&&b| gcd(a, b) != 1) {
+            return Err(format!("the bases {} and {} are not coprime", a, b));
+        }
+    }
+    Ok(())
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Rotates every point of a generator of `dim` coordinates by the random rotation drawn
+/// from `seed`.
+pub(crate) fn scrambled(sgen: Box<dyn CylindGen>, seed: u64, dim: usize) -> Box<dyn CylindGen> {
+    Box::new(Scrambled {
+        sgen,
+        seed,
+        rotation: random_rotation(&mut SplitMix64::new(seed), dim),
+    })
+}
+
+/// A generator whose points are all rotated by the same rotation.
+struct Scrambled {
+    sgen: Box<dyn CylindGen>,
+    seed: u64,
+    rotation: Array2<f64>,
+}
+
+impl CylindGen for Scrambled {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.rotation
+            .dot(&Array1::from(self.sgen.pop_vec()))
+            .to_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        self.sgen.reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        self.sgen.dim()
+    }
+
+    fn checkpoint(&self) -> Option<GenState> {
+        let state = self.sgen.checkpoint()?;
+        Some(GenState {
+            scramble: Some(self.seed),
+            ..state
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_defaults_match_constructors() {
+        for n in 1..7 {
+            let mut built = SphereBuilder::new(n).build().unwrap();
+            let mut expected = sphere_gen(n, &PRIME_TABLE);
+            assert_eq!(built.pop_batch(20), expected.pop_batch(20));
+        }
+        let mut built = SphereBuilder::new(5).build_sphere_n().unwrap();
+        assert_eq!(built.pop_vec(), SphereN::new(4, &PRIME_TABLE).pop_vec());
+        let built = SphereBuilder::for_sphere_n(4).build_sphere_n().unwrap();
+        assert_eq!((built.get_n(), built.get_base().len()), (4, 5));
+        let mut built = CylindBuilder::new(4).start_index(7).build().unwrap();
+        let mut expected = CylindN::new(4, &PRIME_TABLE);
+        expected.reseed(7);
+        assert_eq!(built.pop_batch(10), expected.pop_batch(10));
+    }
+
+    #[test]
+    fn test_table_options() {
+        let mut reference = SphereBuilder::new(5).build_sphere_n().unwrap();
+        let mut fine = SphereBuilder::new(5)
+            .grid_size(3000)
+            .inversion(Inversion::Newton { tolerance: 1e-14 })
+            .build_sphere_n()
+            .unwrap();
+        assert_eq!(fine.get_grid_size(), 3000);
+        let s4 = SphereBuilder::new(4)
+            .grid_size(50)
+            .build_sphere_n()
+            .unwrap();
+        assert_eq!(s4.get_tp_minus1().len(), 50);
+        for _ in 0..50 {
+            for (a, b) in reference.pop_vec().iter().zip(fine.pop_vec()) {
+                assert!((a - b).abs() < 1e-3);
+            }
+        }
+        // Newton converges to the same angles whatever the grid
+        let mut coarse = SphereBuilder::new(5)
+            .grid_size(20)
+            .inversion(Inversion::Newton { tolerance: 1e-14 })
+            .build()
+            .unwrap();
+        fine.reseed(0);
+        for _ in 0..50 {
+            for (a, b) in fine.pop_vec().iter().zip(coarse.pop_vec()) {
+                assert!((a - b).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scramble() {
+        let mut plain = SphereBuilder::new(3).build().unwrap();
+        let mut scrambled = SphereBuilder::new(3).scramble(42).build().unwrap();
+        let (p, q) = (plain.pop_batch(10), scrambled.pop_batch(10));
+        assert_ne!(p, q);
+        // a rotation preserves the inner products
+        assert_approx_eq!(p.row(1).dot(&p.row(2)), q.row(1).dot(&q.row(2)));
+        scrambled.reseed(0);
+        assert_eq!(scrambled.pop_batch(10), q);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(SphereBuilder::new(0).build().is_err());
+        assert!(SphereBuilder::new(4).base(&[2, 3, 5]).build().is_err());
+        assert!(SphereBuilder::new(4).base(&[2, 3, 1, 7]).build().is_err());
+        assert!(SphereBuilder::new(4).base(&[2, 3, 9, 7]).build().is_err());
+        assert!(SphereBuilder::new(4).grid_size(1).build().is_err());
+        assert!(SphereBuilder::new(3).grid_size(100).build().is_err());
+        let newton = Inversion::Newton { tolerance: 0.0 };
+        assert!(SphereBuilder::new(4).inversion(newton).build().is_err());
+        assert!(SphereBuilder::new(3).build_sphere_n().is_err());
+        assert!(SphereBuilder::new(4).scramble(1).build_sphere_n().is_err());
+        assert!(CylindBuilder::new(1).build_cylind_n().is_err());
+        assert!(CylindBuilder::new(3).base(&[2, 4, 5]).build().is_err());
+        assert_eq!(CylindBuilder::new(1).build().unwrap().pop_vec().len(), 2);
+    }
+}
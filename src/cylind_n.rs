@@ -1,10 +1,49 @@
-use lds_rs::lds::{Circle, VdCorput};
+use crate::state::GenState;
+use lds_rs::lds::{Circle, Sphere, Sphere3Hopf, VdCorput};
+use ndarray::Array2;
 
 /// Trait for cylindrical coordinate generators.
 /// Implementors provide methods to generate points using cylindrical coordinates.
 pub trait CylindGen {
     fn pop_vec(&mut self) -> Vec<f64>;
     fn reseed(&mut self, seed: usize);
+
+    /// Returns the number of coordinates of the generated points.
+    fn dim(&self) -> usize;
+
+    /// Generates the next `npoints` points as the rows of a two-dimensional array.
+    fn pop_batch(&mut self, npoints: usize) -> Array2<f64> {
+        let dim = self.dim();
+        let mut flat = Vec::with_capacity(npoints * dim);
+        for _ in 0..npoints {
+            flat.extend(self.pop_vec());
+        }
+        Array2::from_shape_vec((npoints, dim), flat).unwrap()
+    }
+
+    /// Returns the configuration and position of the generator, if it can be checkpointed
+    /// (see `GenState`).
+    fn checkpoint(&self) -> Option<GenState> {
+        None
+    }
+}
+
+impl<G: CylindGen + ?Sized> CylindGen for Box<G> {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        (**self).pop_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        (**self).reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        (**self).dim()
+    }
+
+    fn checkpoint(&self) -> Option<GenState> {
+        (**self).checkpoint()
+    }
 }
 
 impl CylindGen for Circle {
@@ -15,12 +54,47 @@ impl CylindGen for Circle {
     fn reseed(&mut self, seed: usize) {
         self.reseed(seed);
     }
+
+    fn dim(&self) -> usize {
+        2
+    }
+}
+
+impl CylindGen for Sphere {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.pop().to_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        self.reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        3
+    }
+}
+
+impl CylindGen for Sphere3Hopf {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.pop().to_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        self.reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        4
+    }
 }
 
 /** Generate using cylindrical coordinate method */
 pub struct CylindN {
     vdc: VdCorput,
     c_gen: Box<dyn CylindGen>,
+    n: usize,
+    base: Vec<usize>,
+    index: usize,
 }
 
 /// Generate N-Sphere using cylindrical coordinate method */
@@ -64,14 +138,37 @@ impl CylindN {
         CylindN {
             vdc: VdCorput::new(base[0]),
             c_gen,
+            n,
+            base: base[..n].to_vec(),
+            index: 0,
         }
     }
+
+    /// Returns the position in the sequence: the seed of the last `reseed` plus the number
+    /// of points generated since. Reseeding a new generator with it continues the stream.
+    #[inline]
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the dimension `n` the generator was constructed with.
+    #[inline]
+    pub fn get_n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the bases of the underlying sequences.
+    #[inline]
+    pub fn get_base(&self) -> &[usize] {
+        &self.base
+    }
 }
 
 impl CylindGen for CylindN {
     /// Generates the next point on the n-dimensional cylinder.
     #[inline]
     fn pop_vec(&mut self) -> Vec<f64> {
+        self.index += 1;
         let cosphi = 2.0 * self.vdc.pop() - 1.0; // map to [-1, 1];
         let sinphi = (1.0 - cosphi * cosphi).sqrt();
         let mut res = self.c_gen.pop_vec();
@@ -85,7 +182,17 @@ impl CylindGen for CylindN {
     /// Reseeds the underlying generators with the given seed.
     #[inline]
     fn reseed(&mut self, seed: usize) {
+        self.index = seed;
         self.vdc.reseed(seed);
         self.c_gen.reseed(seed);
     }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.n + 1
+    }
+
+    fn checkpoint(&self) -> Option<GenState> {
+        Some(self.state())
+    }
 }
@@ -0,0 +1,164 @@
+use crate::cylind_n::CylindGen;
+use crate::rng::{random_rotation, SplitMix64};
+use ndarray::{Array1, Array2};
+use rand::distributions::Distribution;
+use rand::Rng;
+use std::cell::RefCell;
+
+/// Exposes a low-discrepancy generator as a `rand` distribution of points.
+///
+/// `Distribution::sample` takes `&self`, so the generator is kept in a `RefCell` and each
+/// sample is the next point of the sequence: `rng.sample_iter(&dist)` walks the sequence in
+/// order and keeps its low discrepancy. Samples are available as `Vec<f64>` or as `[f64; N]`
+/// when `N` is the dimension of the points.
+///
+/// In the plain mode the `Rng` is not used at all. In the randomized-QMC mode, built with
+/// `randomized`, the `Rng` draws a random rotation applied to every point: each replicate
+/// is still a rotated low-discrepancy point set, while the estimates of independent
+/// replicates are unbiased and their spread gives an error estimate.
+///
+/// # Examples
+///
+/// ```
+/// use rand::distributions::Distribution;
+/// use rand::rngs::mock::StepRng;
+/// use sphere_n_rs::distribution::QmcDistribution;
+/// use sphere_n_rs::{CylindGen, SphereN};
+///
+/// let dist = QmcDistribution::new(SphereN::new(3, &[2, 3, 5, 7]));
+/// let mut rng = StepRng::new(0, 1);
+/// let pts: Vec<[f64; 5]> = dist.sample_iter(&mut rng).take(4).collect();
+/// assert_eq!(pts[0].to_vec(), SphereN::new(3, &[2, 3, 5, 7]).pop_vec());
+/// ```
+pub struct QmcDistribution<G: CylindGen> {
+    sgen: RefCell<G>,
+    start: usize,
+    seed: Option<u64>,
+    rotation: RefCell<Option<Array2<f64>>>,
+}
+
+impl<G: CylindGen> QmcDistribution<G> {
+    /// Wraps a generator; the samples are its points, in order, from its current position.
+    pub fn new(sgen: G) -> Self {
+        // generators that cannot report their position are taken to start at 0
+        let start = sgen.checkpoint().map_or(0, |state| state.index);
+        QmcDistribution {
+            sgen: RefCell::new(sgen),
+            start,
+            seed: None,
+            rotation: RefCell::new(None),
+        }
+    }
+
+    /// Wraps a generator in the randomized-QMC mode, with a random rotation drawn from `rng`.
+    pub fn randomized<R: Rng + ?Sized>(sgen: G, rng: &mut R) -> Self {
+        let mut res = Self::new(sgen);
+        res.seed = Some(rng.gen());
+        res
+    }
+
+    /// Starts a new independent replicate: draws a new rotation from `rng` and restarts the
+    /// sequence at the position the generator had when it was wrapped (as reported by
+    /// `CylindGen::checkpoint`, or 0 for generators that cannot be checkpointed).
+    pub fn rerandomize<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.seed = Some(rng.gen());
+        *self.rotation.get_mut() = None;
+        self.sgen.get_mut().reseed(self.start);
+    }
+
+    /// Returns the wrapped generator.
+    pub fn into_inner(self) -> G {
+        self.sgen.into_inner()
+    }
+
+    fn next_point(&self) -> Vec<f64> {
+        let point = self.sgen.borrow_mut().pop_vec();
+        let Some(seed) = self.seed else {
+            return point;
+        };
+        // the dimension is only known from the first point
+        let mut rotation = self.rotation.borrow_mut();
+        let rotation = rotation
+            .get_or_insert_with(|| random_rotation(&mut SplitMix64::new(seed), point.len()));
+        rotation.dot(&Array1::from(point)).to_vec()
+    }
+}
+
+impl<G: CylindGen> Distribution<Vec<f64>> for QmcDistribution<G> {
+    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> Vec<f64> {
+        self.next_point()
+    }
+}
+
+impl<G: CylindGen, const N: usize> Distribution<[f64; N]> for QmcDistribution<G> {
+    /// Panics if the points do not have `N` coordinates.
+    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> [f64; N] {
+        let point = self.next_point();
+        point.try_into().unwrap_or_else(|p: Vec<f64>| {
+            panic!("expected points with {} coordinates, found {}", N, p.len())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::SphereBuilder;
+    use crate::CylindN;
+    use approx_eq::assert_approx_eq;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn test_plain_samples_follow_sequence() {
+        let dist = QmcDistribution::new(SphereBuilder::new(4).build().unwrap());
+        let mut rng = StepRng::new(0, 1);
+        let pts: Vec<Vec<f64>> = (&dist).sample_iter(&mut rng).take(10).collect();
+        let mut expected = dist.into_inner();
+        expected.reseed(0);
+        let expected = expected.pop_batch(10);
+        for (p, e) in pts.iter().zip(expected.rows()) {
+            assert_eq!(p, &e.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_randomized_replicates() {
+        let mut rng = StepRng::new(7, 0x9E37_79B9_7F4A_7C15);
+        let mut dist = QmcDistribution::randomized(CylindN::new(2, &[2, 3]), &mut rng);
+        let first: Vec<[f64; 3]> = (&dist).sample_iter(&mut rng).take(50).collect();
+        dist.rerandomize(&mut rng);
+        let second: Vec<[f64; 3]> = (&dist).sample_iter(&mut rng).take(50).collect();
+        assert_ne!(first[0], second[0]);
+        // both replicates are rotations of the same point set
+        let dot = |a: &[f64; 3], b: &[f64; 3]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+        assert_approx_eq!(dot(&first[3], &first[8]), dot(&second[3], &second[8]));
+        assert_approx_eq!(dot(&first[5], &first[5]), 1.0);
+    }
+
+    #[test]
+    fn test_rerandomize_keeps_start() {
+        let mut rng = StepRng::new(3, 0x9E37_79B9_7F4A_7C15);
+        let sgen = SphereBuilder::new(4).start_index(40).build().unwrap();
+        let mut dist = QmcDistribution::randomized(sgen, &mut rng);
+        let first: Vec<Vec<f64>> = (&dist).sample_iter(&mut rng).take(8).collect();
+        dist.rerandomize(&mut rng);
+        let second: Vec<Vec<f64>> = (&dist).sample_iter(&mut rng).take(2).collect();
+        assert_ne!(first[0], second[0]);
+        // both replicates are rotations of the points from index 40 on
+        let mut plain = SphereBuilder::new(4).start_index(40).build().unwrap();
+        let expected = [plain.pop_vec(), plain.pop_vec()];
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+        let angle = dot(&expected[0], &expected[1]);
+        assert!((dot(&first[0], &first[1]) - angle).abs() < 1e-12);
+        assert!((dot(&second[0], &second[1]) - angle).abs() < 1e-12);
+        dist.rerandomize(&mut rng);
+        assert_eq!(dist.into_inner().checkpoint().unwrap().index, 40);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_array_size_mismatch() {
+        let dist = QmcDistribution::new(CylindN::new(2, &[2, 3]));
+        let _: [f64; 4] = dist.sample(&mut StepRng::new(0, 1));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::sphere_gen;
+use ndarray::{Array1, Array2};
+
+/// Generate low-discrepancy points on the surface of an axis-aligned ellipsoid.
+///
+/// Points of a uniform sphere generator are stretched by the semi-axes `a_i`, which maps
+/// `S^n` onto the ellipsoid `sum (y_i / a_i)^2 = 1` but distorts the area: the image of the
+/// sphere point `x` carries the area element `prod(a_i) * sqrt(sum (x_i / a_i)^2)` times
+/// the spherical one. `pop_weighted` returns this factor as the weight of each point, so that
+/// surface integrals are estimated without rejection by `A_n * mean(w * g)` (with `A_n` the
+/// area of the unit sphere), and surface averages by `sum(w * g) / sum(w)`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::ellipsoid::Ellipsoid;
+/// use sphere_n_rs::metrics::sphere_area;
+///
+/// let mut egen = Ellipsoid::new(&[2.0, 2.0, 1.0], &[2, 3]);
+/// let (pts, weights) = egen.pop_batch_weighted(1000);
+/// assert_eq!(pts.dim(), (1000, 3));
+/// let area = sphere_area(2) * weights.mean().unwrap();
+/// assert!((area - 34.68).abs() < 0.1);
+/// ```
+pub struct Ellipsoid {
+    s_gen: Box<dyn CylindGen>,
+    semi_axes: Vec<f64>,
+    volume_factor: f64,
+}
+
+impl Ellipsoid {
+    /// The function `new` constructs a new `Ellipsoid` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `semi_axes`: The positive semi-axes `a_0, ..., a_n` of an ellipsoid in `R^(n+1)`.
+    /// * `base`: The bases of the underlying uniform generator of `S^n` (see `sphere_gen`).
+    ///
+    /// Returns:
+    ///
+    /// A new `Ellipsoid` object is being returned from the `new` function.
+    pub fn new(semi_axes: &[f64], base: &[usize]) -> Self {
+        assert!(semi_axes.len() >= 2);
+        Self::from_gen(semi_axes, sphere_gen(semi_axes.len() - 1, base))
+    }
+
+    /// Builds the ellipsoid sampler on top of an existing sphere generator, whose points
+    /// must have `semi_axes.len()` coordinates. The area weights assume that the points are
+    /// uniform on the sphere: use `SphereN` or `sphere_gen`, or `CylindN` only for `n = 2`.
+    pub fn from_gen(semi_axes: &[f64], s_gen: Box<dyn CylindGen>) -> Self {
+        assert!(semi_axes.iter().all(|&a| a > 0.0));
+        Ellipsoid {
+            s_gen,
+            semi_axes: semi_axes.to_vec(),
+            volume_factor: semi_axes.iter().product(),
+        }
+    }
+
+    /// Generates the next point on the ellipsoid together with its area weight.
+    pub fn pop_weighted(&mut self) -> (Vec<f64>, f64) {
+        let mut res = self.s_gen.pop_vec();
+        assert_eq!(res.len(), self.semi_axes.len());
+        let mut sum = 0.0;
+        for (xi, a) in res.iter_mut().zip(&self.semi_axes) {
+            sum += (*xi / a).powi(2);
+            *xi *= a;
+        }
+        (res, self.volume_factor * sum.sqrt())
+    }
+
+    /// Generates the next `npoints` points as the rows of a two-dimensional array, with
+    /// their area weights.
+    pub fn pop_batch_weighted(&mut self, npoints: usize) -> (Array2<f64>, Array1<f64>) {
+        let dim = self.semi_axes.len();
+        let mut pts = Array2::zeros((npoints, dim));
+        let mut weights = Array1::zeros(npoints);
+        for (mut row, w) in pts.rows_mut().into_iter().zip(weights.iter_mut()) {
+            let (pt, weight) = self.pop_weighted();
+            row.assign(&Array1::from(pt));
+            *w = weight;
+        }
+        (pts, weights)
+    }
+
+    /// Returns the semi-axes of the ellipsoid.
+    #[inline]
+    pub fn get_semi_axes(&self) -> &[f64] {
+        &self.semi_axes
+    }
+}
+
+impl CylindGen for Ellipsoid {
+    /// Generates the next point on the ellipsoid, dropping its weight.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.pop_weighted().0
+    }
+
+    /// Reseeds the underlying sphere generator with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.s_gen.reseed(seed);
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.semi_axes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::sphere_area;
+    use crate::CylindN;
+    use approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_ellipsoid_on_surface() {
+        let axes = [3.0, 1.0, 0.5, 2.0];
+        let mut egen = Ellipsoid::new(&axes, &[2, 3, 5, 7]);
+        for _ in 0..200 {
+            let (pt, w) = egen.pop_weighted();
+            let level: f64 = pt.iter().zip(&axes).map(|(y, a)| (y / a).powi(2)).sum();
+            assert_approx_eq!(level, 1.0);
+            assert!(w > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_scaled_sphere_weights() {
+        // a sphere of radius r has the constant weight r^n
+        let mut egen = Ellipsoid::from_gen(&[1.5, 1.5, 1.5], Box::new(CylindN::new(2, &[2, 3])));
+        for _ in 0..50 {
+            assert_approx_eq!(egen.pop_weighted().1, 1.5 * 1.5);
+        }
+    }
+
+    #[test]
+    fn test_spheroid_area() {
+        // prolate spheroid with a = 1 (equatorial) and c = 2 (polar):
+        // S = 2 pi a^2 (1 + c / (a e) asin(e)), e^2 = 1 - a^2 / c^2
+        let (a, c) = (1.0_f64, 2.0_f64);
+        let e = (1.0 - a * a / (c * c)).sqrt();
+        let exact = 2.0 * std::f64::consts::PI * a * a * (1.0 + c / (a * e) * e.asin());
+        let mut egen = Ellipsoid::new(&[a, a, c], &[2, 3]);
+        let (_, weights) = egen.pop_batch_weighted(4000);
+        let area = sphere_area(2) * weights.mean().unwrap();
+        assert!((area - exact).abs() < 1e-3 * exact);
+    }
+}
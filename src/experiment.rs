@@ -0,0 +1,294 @@
+//! Reproducible comparison of point generators on `S^n`.
+//!
+//! An experiment sweeps over sphere dimensions and point counts, generates the first `N`
+//! points of every requested [`Method`] and evaluates the quality metrics of
+//! [`crate::metrics`] on them. The results can be written out as a CSV table.
+//!
+//! # Examples
+//!
+//! ```
+//! use sphere_n_rs::experiment::{run_experiment, write_csv, ExperimentConfig, Method};
+//!
+//! let config = ExperimentConfig {
+//!     dims: vec![2],
+//!     npoints: vec![100],
+//!     methods: vec![Method::SphereN, Method::Random],
+//!     ..Default::default()
+//! };
+//! let rows = run_experiment(&config);
+//! assert_eq!(rows.len(), 2);
+//!
+//! let mut out = Vec::new();
+//! write_csv(&rows, &mut out).unwrap();
+//! assert!(String::from_utf8(out).unwrap().starts_with("method,n,npoints"));
+//! ```
+
+use crate::cylind_n::{CylindGen, CylindN};
+use crate::metrics::{cap_discrepancy, covering_radius, dispersion, min_separation, riesz_energy};
+use crate::rng::random_sphere_points;
+use crate::sphere_n::sphere_gen;
+use lds_rs::lds::{Sphere3Hopf, PRIME_TABLE};
+use ndarray::Array2;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// A method of placing points on `S^n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// The recursive sphere method (see [`crate::sphere_n::sphere_gen`]).
+    SphereN,
+    /// The cylindrical coordinate method, [`CylindN`].
+    CylindN,
+    /// Normalized Gaussian pseudo-random samples.
+    Random,
+    /// The spherical Fibonacci lattice (`S^2` only, not extensible in `N`).
+    Fibonacci,
+    /// Hopf coordinates from `lds_rs::Sphere3Hopf` (`S^3` only).
+    Hopf,
+}
+
+impl Method {
+    /// All available methods.
+    pub const ALL: [Method; 5] = [
+        Method::SphereN,
+        Method::CylindN,
+        Method::Random,
+        Method::Fibonacci,
+        Method::Hopf,
+    ];
+
+    /// Returns the name used in the CSV output and accepted by `from_str`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::SphereN => "sphere_n",
+            Method::CylindN => "cylind_n",
+            Method::Random => "random",
+            Method::Fibonacci => "fibonacci",
+            Method::Hopf => "hopf",
+        }
+    }
+
+    /// Returns whether the method can generate points on `S^n`.
+    pub fn supports(&self, n: usize) -> bool {
+        match self {
+            Method::SphereN | Method::Random => n >= 1,
+            Method::CylindN => n >= 2,
+            Method::Fibonacci => n == 2,
+            Method::Hopf => n == 3,
+        }
+    }
+
+    /// Generates the first `npoints` points of the method on `S^n`, one point per row.
+    ///
+    /// The low-discrepancy methods use consecutive bases from `PRIME_TABLE`; `seed` only
+    /// affects the pseudo-random method. Returns `None` if `n` is not supported.
+    pub fn points(&self, n: usize, npoints: usize, seed: u64) -> Option<Array2<f64>> {
+        if !self.supports(n) {
+            return None;
+        }
+        let pts = match self {
+            Method::SphereN => sphere_gen(n, &PRIME_TABLE).pop_batch(npoints),
+            Method::CylindN => CylindN::new(n, &PRIME_TABLE).pop_batch(npoints),
+            Method::Random => random_sphere_points(n, npoints, seed),
+            Method::Fibonacci => fibonacci_sphere(npoints),
+            Method::Hopf => Sphere3Hopf::new(&PRIME_TABLE).pop_batch(npoints),
+        };
+        Some(pts)
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Method::ALL
+            .iter()
+            .find(|m| m.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown method `{}`", s))
+    }
+}
+
+/// Generates `npoints` points of the spherical Fibonacci lattice on `S^2`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::experiment::fibonacci_sphere;
+///
+/// let pts = fibonacci_sphere(10);
+/// assert_eq!(pts.dim(), (10, 3));
+/// ```
+pub fn fibonacci_sphere(npoints: usize) -> Array2<f64> {
+    let golden = (1.0 + 5.0_f64.sqrt()) / 2.0;
+    let mut res = Array2::zeros((npoints, 3));
+    for (i, mut row) in res.rows_mut().into_iter().enumerate() {
+        let z = 1.0 - (2 * i + 1) as f64 / npoints as f64;
+        let r = (1.0 - z * z).sqrt();
+        let phi = TAU * (i as f64 / golden).fract();
+        row[0] = r * phi.cos();
+        row[1] = r * phi.sin();
+        row[2] = z;
+    }
+    res
+}
+
+/// The configuration of a comparison experiment.
+#[derive(Debug, Clone)]
+pub struct ExperimentConfig {
+    /// The sphere dimensions `n` to sweep (points on `S^n`).
+    pub dims: Vec<usize>,
+    /// The point counts `N` to sweep.
+    pub npoints: Vec<usize>,
+    /// The methods to compare; unsupported (method, dimension) pairs are skipped.
+    pub methods: Vec<Method>,
+    /// The number of caps probed by the cap discrepancy estimate.
+    pub ncaps: usize,
+    /// The number of probe points of the covering radius estimate.
+    pub nprobes: usize,
+    /// The seed of the pseudo-random method and of the metric estimates.
+    pub seed: u64,
+    /// The largest sphere dimension for which the hull-based dispersion is computed.
+    pub max_dispersion_dim: usize,
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        ExperimentConfig {
+            dims: vec![2, 3, 4],
+            npoints: vec![100, 200, 400, 800, 1600],
+            methods: Method::ALL.to_vec(),
+            ncaps: 200,
+            nprobes: 2000,
+            seed: 0,
+            max_dispersion_dim: 3,
+        }
+    }
+}
+
+/// The metrics of one method for one dimension and point count.
+#[derive(Debug, Clone)]
+pub struct ExperimentRow {
+    pub method: Method,
+    /// The sphere dimension (points on `S^n`).
+    pub n: usize,
+    pub npoints: usize,
+    pub cap_discrepancy: f64,
+    pub min_separation: f64,
+    pub covering_radius: f64,
+    /// The Riesz `(n-1)`-energy (logarithmic energy on `S^1`) divided by `N^2`.
+    pub energy: f64,
+    /// The hull-based dispersion, if computed for this dimension.
+    pub dispersion: Option<f64>,
+}
+
+/// Runs the experiment described by `config`.
+///
+/// Returns:
+///
+/// One row per supported (dimension, point count, method) triple, in sweep order.
+pub fn run_experiment(config: &ExperimentConfig) -> Vec<ExperimentRow> {
+    let mut rows = Vec::new();
+    for &n in &config.dims {
+        for &npoints in &config.npoints {
+            for method in &config.methods {
+                let pts = match method.points(n, npoints, config.seed) {
+                    Some(pts) => pts,
+                    None => continue,
+                };
+                let view = pts.view();
+                let dispersion = if n <= config.max_dispersion_dim {
+                    Some(dispersion(view))
+                } else {
+                    None
+                };
+                rows.push(ExperimentRow {
+                    method: *method,
+                    n,
+                    npoints,
+                    cap_discrepancy: cap_discrepancy(view, config.ncaps, config.seed),
+                    min_separation: min_separation(view),
+                    covering_radius: covering_radius(view, config.nprobes, config.seed),
+                    energy: riesz_energy(view, (n - 1) as f64) / (npoints * npoints) as f64,
+                    dispersion,
+                });
+            }
+        }
+    }
+    rows
+}
+
+/// Writes experiment results as a CSV table with a header row.
+pub fn write_csv<W: Write>(rows: &[ExperimentRow], mut out: W) -> io::Result<()> {
+    writeln!(
+        out,
+        "method,n,npoints,cap_discrepancy,min_separation,covering_radius,energy,dispersion"
+    )?;
+    for row in rows {
+        let dispersion = row.dispersion.map(|d| d.to_string()).unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            row.method,
+            row.n,
+            row.npoints,
+            row.cap_discrepancy,
+            row.min_separation,
+            row.covering_radius,
+            row.energy,
+            dispersion
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_methods_on_sphere() {
+        for n in 1..6 {
+            for method in Method::ALL {
+                if let Some(pts) = method.points(n, 50, 1) {
+                    assert_eq!(pts.dim(), (50, n + 1));
+                    for row in pts.rows() {
+                        assert_approx_eq!(row.dot(&row), 1.0);
+                    }
+                }
+            }
+        }
+        assert!(Method::Fibonacci.points(3, 10, 0).is_none());
+        assert_eq!("cylind_n".parse::<Method>(), Ok(Method::CylindN));
+        assert!("qmc".parse::<Method>().is_err());
+    }
+
+    #[test]
+    fn test_lds_beats_random() {
+        let config = ExperimentConfig {
+            dims: vec![2, 4],
+            npoints: vec![500],
+            methods: vec![Method::SphereN, Method::Random],
+            ncaps: 100,
+            nprobes: 1000,
+            ..Default::default()
+        };
+        let rows = run_experiment(&config);
+        assert_eq!(rows.len(), 4);
+        for pair in rows.chunks(2) {
+            let (lds, rand) = (&pair[0], &pair[1]);
+            assert!(lds.cap_discrepancy < rand.cap_discrepancy);
+            assert!(lds.min_separation > rand.min_separation);
+        }
+        assert!(rows[0].dispersion.is_some() && rows[2].dispersion.is_none());
+    }
+}
@@ -0,0 +1,333 @@
+//! Spherical harmonics on `S^2` and zonal harmonics on `S^n`.
+//!
+//! The real spherical harmonics `Y_lm` are orthonormal over `S^2` and are indexed by
+//! `l >= 0` and `-l <= m <= l`, with `m < 0` selecting the `sin(|m| phi)` functions. On
+//! higher-dimensional spheres only zonal harmonics (functions of `x . y` for a fixed pole
+//! `y`) are provided, built from Gegenbauer polynomials.
+
+use crate::metrics::sphere_area;
+use ndarray::ArrayView2;
+use std::f64::consts::PI;
+
+/// Returns the position of `Y_lm` in a coefficient vector ordered by degree, then order:
+/// `l^2 + l + m`.
+#[inline]
+pub fn sh_index(l: usize, m: i64) -> usize {
+    assert!(m.unsigned_abs() as usize <= l);
+    ((l * l + l) as i64 + m) as usize
+}
+
+/// Evaluates the fully normalized associated Legendre function `N_lm P_l^m(x)`, without
+/// the Condon-Shortley phase, by the standard three-term recurrence in `l`.
+fn normalized_legendre(l: usize, m: usize, x: f64) -> f64 {
+    let sin_t = (1.0 - x * x).max(0.0).sqrt();
+    let mut pmm = (0.25 / PI).sqrt();
+    for k in 1..=m {
+        pmm *= ((2 * k + 1) as f64 / (2 * k) as f64).sqrt() * sin_t;
+    }
+    if l == m {
+        return pmm;
+    }
+    let mut prev = pmm;
+    let mut cur = x * ((2 * m + 3) as f64).sqrt() * pmm;
+    for k in m + 2..=l {
+        let (kf, mf) = (k as f64, m as f64);
+        let a = ((4.0 * kf * kf - 1.0) / (kf * kf - mf * mf)).sqrt();
+        let b =
+            (((kf - 1.0) * (kf - 1.0) - mf * mf) / (4.0 * (kf - 1.0) * (kf - 1.0) - 1.0)).sqrt();
+        let next = a * (x * cur - b * prev);
+        prev = cur;
+        cur = next;
+    }
+    cur
+}
+
+/// Evaluates the real spherical harmonic `Y_lm` at a unit vector of `R^3`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::harmonics::real_sph_harm;
+/// use approx_eq::assert_approx_eq;
+///
+/// // Y_10 = sqrt(3 / (4 pi)) z
+/// let y = real_sph_harm(1, 0, &[0.0, 0.6, 0.8]);
+/// assert_approx_eq!(y, (3.0 / (4.0 * std::f64::consts::PI)).sqrt() * 0.8);
+/// ```
+pub fn real_sph_harm(l: usize, m: i64, point: &[f64]) -> f64 {
+    let am = m.unsigned_abs() as usize;
+    assert!(am <= l);
+    let plm = normalized_legendre(l, am, point[2].clamp(-1.0, 1.0));
+    if m == 0 {
+        return plm;
+    }
+    let phi = point[1].atan2(point[0]);
+    let trig = if m > 0 {
+        (am as f64 * phi).cos()
+    } else {
+        (am as f64 * phi).sin()
+    };
+    std::f64::consts::SQRT_2 * plm * trig
+}
+
+/// Evaluates all real spherical harmonics up to degree `lmax` at a unit vector of `R^3`,
+/// ordered as in [`sh_index`].
+pub fn real_sph_harm_all(lmax: usize, point: &[f64]) -> Vec<f64> {
+    let mut res = Vec::with_capacity((lmax + 1) * (lmax + 1));
+    for l in 0..=lmax {
+        for m in -(l as i64)..=l as i64 {
+            res.push(real_sph_harm(l, m, point));
+        }
+    }
+    res
+}
+
+/// Evaluates the Gegenbauer polynomial `C_l^(alpha)(t)` by its three-term recurrence.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::harmonics::gegenbauer;
+/// use approx_eq::assert_approx_eq;
+///
+/// // C_l^(1/2) are the Legendre polynomials: P_2(t) = (3 t^2 - 1) / 2
+/// assert_approx_eq!(gegenbauer(2, 0.5, 0.3), (3.0 * 0.09 - 1.0) / 2.0);
+/// ```
+pub fn gegenbauer(l: usize, alpha: f64, t: f64) -> f64 {
+    if l == 0 {
+        return 1.0;
+    }
+    let mut prev = 1.0;
+    let mut cur = 2.0 * alpha * t;
+    for k in 2..=l {
+        let kf = k as f64;
+        let next = (2.0 * t * (kf + alpha - 1.0) * cur - (kf + 2.0 * alpha - 2.0) * prev) / kf;
+        prev = cur;
+        cur = next;
+    }
+    cur
+}
+
+/// Returns the dimension of the space of spherical harmonics of degree `l` on `S^n`.
+pub fn harmonic_dimension(n: usize, l: usize) -> usize {
+    assert!(n >= 1);
+    if l == 0 {
+        return 1;
+    }
+    // (2l + n - 1) / (l + n - 1) * binom(l + n - 1, l)
+    let mut binom: usize = 1;
+    for k in 1..=l {
+        binom = binom * (n - 1 + k) / k;
+    }
+    binom * (2 * l + n - 1) / (l + n - 1)
+}
+
+/// Evaluates the zonal harmonic of degree `l` on `S^n` at `t = x . y`.
+///
+/// The zonal harmonic is the reproducing kernel of the degree-`l` harmonics: for every
+/// harmonic `h` of degree `l`, `h(y) = integral over S^n of h(x) Z_l(x . y) dx`. It is
+/// proportional to the Gegenbauer polynomial `C_l^((n-1)/2)(t)` (to `cos(l acos t)` on
+/// `S^1`).
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::harmonics::zonal_harmonic;
+/// use approx_eq::assert_approx_eq;
+///
+/// // on S^2: Z_l(t) = (2l + 1) / (4 pi) P_l(t)
+/// assert_approx_eq!(zonal_harmonic(2, 1, 0.3), 3.0 / (4.0 * std::f64::consts::PI) * 0.3);
+/// ```
+pub fn zonal_harmonic(n: usize, l: usize, t: f64) -> f64 {
+    assert!(n >= 1);
+    let t = t.clamp(-1.0, 1.0);
+    let scale = harmonic_dimension(n, l) as f64 / sphere_area(n);
+    if n == 1 {
+        return scale * (l as f64 * t.acos()).cos();
+    }
+    let alpha = (n - 1) as f64 / 2.0;
+    scale * gegenbauer(l, alpha, t) / gegenbauer(l, alpha, 1.0)
+}
+
+/// Estimates the real spherical harmonic coefficients of `f` up to degree `lmax` from a
+/// point set uniformly distributed on `S^2`.
+///
+/// Each coefficient `c_lm = integral of f Y_lm` is estimated by the quasi-Monte Carlo rule
+/// `4 pi / N * sum_i f(x_i) Y_lm(x_i)`.
+///
+/// Arguments:
+///
+/// * `f`: The function to project.
+/// * `points`: The sample points on `S^2`, one per row.
+/// * `lmax`: The largest degree.
+///
+/// Returns:
+///
+/// The `(lmax + 1)^2` coefficients ordered as in [`sh_index`].
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::harmonics::{sh_coefficients, sh_index};
+/// use sphere_n_rs::sphere_n::sphere_gen;
+///
+/// let mut sgen = sphere_gen(2, &[2, 3]);
+/// let pts = sgen.pop_batch(4000);
+/// let coeffs = sh_coefficients(|x| x[2], pts.view(), 2);
+/// // z = sqrt(4 pi / 3) Y_10
+/// assert!((coeffs[sh_index(1, 0)] - (4.0 * std::f64::consts::PI / 3.0).sqrt()).abs() < 1e-3);
+/// assert!(coeffs[sh_index(2, 1)].abs() < 1e-3);
+/// ```
+pub fn sh_coefficients<F>(f: F, points: ArrayView2<f64>, lmax: usize) -> Vec<f64>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    assert_eq!(points.ncols(), 3);
+    let mut res = vec![0.0; (lmax + 1) * (lmax + 1)];
+    for row in points.rows() {
+        let pt = row.to_vec();
+        let value = f(&pt);
+        for (c, y) in res.iter_mut().zip(real_sph_harm_all(lmax, &pt)) {
+            *c += value * y;
+        }
+    }
+    let weight = 4.0 * PI / points.nrows() as f64;
+    res.iter_mut().for_each(|c| *c *= weight);
+    res
+}
+
+/// Estimates the degree-`l` components of `f` at the point `pole` of `S^n`, for
+/// `l = 0..=lmax`, from a point set uniformly distributed on `S^n`.
+///
+/// The component `(P_l f)(pole) = integral of f(x) Z_l(x . pole) dx` is estimated by the
+/// quasi-Monte Carlo rule over `points`. Summing the components over `l` reconstructs `f`
+/// at `pole` for band-limited functions.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::harmonics::zonal_projection;
+/// use sphere_n_rs::sphere_n::sphere_gen;
+///
+/// let mut sgen = sphere_gen(4, &[2, 3, 5, 7]);
+/// let pts = sgen.pop_batch(4000);
+/// let pole = [0.0, 0.0, 0.0, 0.6, 0.8];
+/// // a degree-1 function is its own degree-1 component
+/// let comps = zonal_projection(|x| x[4], pts.view(), &pole, 2);
+/// assert!(comps[0].abs() < 1e-2 && comps[2].abs() < 1e-2);
+/// assert!((comps[1] - 0.8).abs() < 1e-2);
+/// ```
+pub fn zonal_projection<F>(f: F, points: ArrayView2<f64>, pole: &[f64], lmax: usize) -> Vec<f64>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    assert_eq!(points.ncols(), pole.len());
+    let n = pole.len() - 1;
+    let mut res = vec![0.0; lmax + 1];
+    for row in points.rows() {
+        let pt = row.to_vec();
+        let value = f(&pt);
+        let t: f64 = pt.iter().zip(pole).map(|(a, b)| a * b).sum();
+        for (l, c) in res.iter_mut().enumerate() {
+            *c += value * zonal_harmonic(n, l, t);
+        }
+    }
+    let weight = sphere_area(n) / points.nrows() as f64;
+    res.iter_mut().for_each(|c| *c *= weight);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere_n::sphere_gen;
+    use approx_eq::assert_approx_eq;
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_low_degree_harmonics_vanish() {
+        // under the crate's points on S^2 every harmonic of degree >= 1 averages to ~0
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let pts = sgen.pop_batch(5000);
+        let lmax = 6;
+        let mut means = vec![0.0; (lmax + 1) * (lmax + 1)];
+        for row in pts.rows() {
+            for (m, y) in means.iter_mut().zip(real_sph_harm_all(lmax, &row.to_vec())) {
+                *m += y / pts.nrows() as f64;
+            }
+        }
+        assert_approx_eq!(means[0], (0.25 / PI).sqrt());
+        assert!(means[1..].iter().all(|m| m.abs() < 2e-3), "{:?}", means);
+
+        // and so does every zonal harmonic of degree >= 1 on higher spheres
+        for n in [3, 4, 5] {
+            let mut sgen = sphere_gen(n, &PRIME_TABLE);
+            let pts = sgen.pop_batch(5000);
+            let pole: Vec<f64> = (0..=n).map(|i| if i == 0 { 1.0 } else { 0.0 }).collect();
+            let comps = zonal_projection(|_| 1.0, pts.view(), &pole, 4);
+            assert_approx_eq!(comps[0], 1.0);
+            for (l, c) in comps.iter().enumerate().skip(1) {
+                // relative to the peak value Z_l(1) of the kernel
+                assert!(c.abs() < 2.5e-2 * zonal_harmonic(n, l, 1.0), "{:?}", comps);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sh_orthonormal() {
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let pts = sgen.pop_batch(20000);
+        let lmax = 3;
+        let size = (lmax + 1) * (lmax + 1);
+        let mut gram = vec![0.0; size * size];
+        for row in pts.rows() {
+            let ys = real_sph_harm_all(lmax, &row.to_vec());
+            for i in 0..size {
+                for j in 0..size {
+                    gram[i * size + j] += 4.0 * PI * ys[i] * ys[j] / pts.nrows() as f64;
+                }
+            }
+        }
+        for i in 0..size {
+            for j in 0..size {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((gram[i * size + j] - expected).abs() < 5e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sh_projection() {
+        let f = |x: &[f64]| 2.0 * real_sph_harm(2, -1, x) - 0.5 * real_sph_harm(3, 2, x) + 1.0;
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let pts = sgen.pop_batch(10000);
+        let coeffs = sh_coefficients(f, pts.view(), 4);
+        for l in 0..=4 {
+            for m in -(l as i64)..=l as i64 {
+                let expected = match (l, m) {
+                    (0, 0) => (4.0 * PI).sqrt(),
+                    (2, -1) => 2.0,
+                    (3, 2) => -0.5,
+                    _ => 0.0,
+                };
+                assert!((coeffs[sh_index(l, m)] - expected).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zonal_reproducing() {
+        // the zonal harmonics of S^2 match the addition theorem
+        let (x, y) = ([0.0, 0.6, 0.8], [0.48, 0.6, 0.64]);
+        let t: f64 = x.iter().zip(&y).map(|(a, b)| a * b).sum();
+        for l in 0..5 {
+            let sum: f64 = (-(l as i64)..=l as i64)
+                .map(|m| real_sph_harm(l, m, &x) * real_sph_harm(l, m, &y))
+                .sum();
+            assert_approx_eq!(zonal_harmonic(2, l, t), sum);
+        }
+        assert_eq!(harmonic_dimension(2, 3), 7);
+        assert_eq!(harmonic_dimension(3, 2), 9);
+        assert_eq!(harmonic_dimension(1, 4), 2);
+    }
+}
@@ -0,0 +1,454 @@
+//! Convex hulls of small-dimensional point sets.
+//!
+//! The hull is computed with the Quickhull algorithm and returned as a list of simplicial
+//! facets. It works in any dimension but is meant for low-dimensional spheres (`S^2` to
+//! `S^4`), where every generated point is a hull vertex and the facet count stays small.
+
+use ndarray::{Array2, ArrayView2};
+use std::collections::HashMap;
+
+const EPS: f64 = 1e-12;
+
+/// A simplicial facet of the hull.
+///
+/// `neighbors[i]` is the facet sharing every vertex except `vertices[i]`.
+struct Facet {
+    vertices: Vec<usize>,
+    neighbors: Vec<usize>,
+    normal: Vec<f64>,
+    offset: f64,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl Facet {
+    #[inline]
+    fn distance(&self, pt: &[f64]) -> f64 {
+        dot(&self.normal, pt) - self.offset
+    }
+}
+
+#[inline]
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Computes the determinant of a small square matrix by Gaussian elimination.
+fn determinant(mut mat: Vec<Vec<f64>>) -> f64 {
+    let n = mat.len();
+    let mut det = 1.0;
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| mat[a][col].abs().total_cmp(&mat[b][col].abs()))
+            .unwrap();
+        if mat[pivot][col] == 0.0 {
+            return 0.0;
+        }
+        if pivot != col {
+            mat.swap(pivot, col);
+            det = -det;
+        }
+        det *= mat[col][col];
+        let (upper, lower) = mat.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *x -= factor * p;
+            }
+        }
+    }
+    det
+}
+
+/// Computes the unit normal of the hyperplane through `d` points in `R^d` via the
+/// generalized cross product of the edge vectors. Returns `None` for degenerate input.
+fn hyperplane_normal(pts: &[&[f64]]) -> Option<Vec<f64>> {
+    let dim = pts[0].len();
+    let edges: Vec<Vec<f64>> = pts[1..]
+        .iter()
+        .map(|p| p.iter().zip(pts[0]).map(|(a, b)| a - b).collect())
+        .collect();
+    let mut normal: Vec<f64> = (0..dim)
+        .map(|j| {
+            let minor = edges
+                .iter()
+                .map(|e| {
+                    e.iter()
+                        .enumerate()
+                        .filter(|&(k, _)| k != j)
+                        .map(|(_, &x)| x)
+                        .collect()
+                })
+                .collect();
+            let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+            sign * determinant(minor)
+        })
+        .collect();
+    let norm = dot(&normal, &normal).sqrt();
+    if norm < EPS {
+        return None;
+    }
+    for x in normal.iter_mut() {
+        *x /= norm;
+    }
+    Some(normal)
+}
+
+struct Quickhull<'a> {
+    points: Vec<&'a [f64]>,
+    interior: Vec<f64>,
+    facets: Vec<Facet>,
+}
+
+impl<'a> Quickhull<'a> {
+    /// Creates a facet through `vertices` oriented away from the interior point.
+    fn make_facet(&self, mut vertices: Vec<usize>) -> Facet {
+        let pts: Vec<&[f64]> = vertices.iter().map(|&i| self.points[i]).collect();
+        let mut normal = hyperplane_normal(&pts).unwrap_or_else(|| vec![0.0; pts[0].len()]);
+        let mut offset = dot(&normal, pts[0]);
+        if dot(&normal, &self.interior) > offset {
+            for x in normal.iter_mut() {
+                *x = -*x;
+            }
+            offset = -offset;
+            // keep the vertex order consistent with the outward orientation
+            let last = vertices.len() - 1;
+            vertices.swap(last - 1, last);
+        }
+        Facet {
+            neighbors: vec![usize::MAX; vertices.len()],
+            vertices,
+            normal,
+            offset,
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    /// Picks `dim + 1` affinely independent points for the initial simplex.
+    fn initial_simplex(&self) -> Option<Vec<usize>> {
+        let dim = self.points[0].len();
+        let mut chosen = vec![0];
+        // Gram-Schmidt on the edge vectors: greedily keep the point furthest from the
+        // affine span of the points chosen so far.
+        let mut basis: Vec<Vec<f64>> = Vec::new();
+        while chosen.len() <= dim {
+            let origin = self.points[chosen[0]];
+            let mut best = (0.0, 0, Vec::new());
+            for (i, p) in self.points.iter().enumerate() {
+                let mut v: Vec<f64> = p.iter().zip(origin).map(|(a, b)| a - b).collect();
+                for b in &basis {
+                    let proj = dot(&v, b);
+                    for (x, y) in v.iter_mut().zip(b) {
+                        *x -= proj * y;
+                    }
+                }
+                let len = dot(&v, &v).sqrt();
+                if len > best.0 {
+                    best = (len, i, v);
+                }
+            }
+            let (len, idx, v) = best;
+            if len < 1e-9 {
+                return None;
+            }
+            basis.push(v.iter().map(|x| x / len).collect());
+            chosen.push(idx);
+        }
+        Some(chosen)
+    }
+
+    /// Adds `idx` to the outside set of the first facet in `candidates` that sees it.
+    fn assign(&mut self, idx: usize, candidates: &[usize]) {
+        let pt = self.points[idx];
+        for &f in candidates {
+            if self.facets[f].distance(pt) > EPS {
+                self.facets[f].outside.push(idx);
+                return;
+            }
+        }
+    }
+
+    fn build(&mut self) -> bool {
+        let dim = self.points[0].len();
+        let simplex = match self.initial_simplex() {
+            Some(s) => s,
+            None => return false,
+        };
+        self.interior = vec![0.0; dim];
+        for &i in &simplex {
+            for (c, x) in self.interior.iter_mut().zip(self.points[i]) {
+                *c += x / (dim + 1) as f64;
+            }
+        }
+        // facet k of the simplex omits vertex simplex[k]; facets k and j are neighbors
+        // across the vertex omitted by the other.
+        for k in 0..=dim {
+            let vertices: Vec<usize> = simplex
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != k)
+                .map(|(_, &v)| v)
+                .collect();
+            let facet = self.make_facet(vertices);
+            self.facets.push(facet);
+        }
+        for k in 0..=dim {
+            for i in 0..dim {
+                let omitted = self.facets[k].vertices[i];
+                let j = simplex.iter().position(|&v| v == omitted).unwrap();
+                self.facets[k].neighbors[i] = j;
+            }
+        }
+        let all: Vec<usize> = (0..=dim).collect();
+        for idx in 0..self.points.len() {
+            if !simplex.contains(&idx) {
+                self.assign(idx, &all);
+            }
+        }
+
+        let mut stack: Vec<usize> = all;
+        while let Some(f) = stack.pop() {
+            if !self.facets[f].alive || self.facets[f].outside.is_empty() {
+                continue;
+            }
+            // the furthest outside point of this facet is a hull vertex
+            let facet = &self.facets[f];
+            let apex = *facet
+                .outside
+                .iter()
+                .max_by(|&&a, &&b| {
+                    facet
+                        .distance(self.points[a])
+                        .total_cmp(&facet.distance(self.points[b]))
+                })
+                .unwrap();
+            let apex_pt = self.points[apex];
+
+            // the facets visible from the apex form a connected region around `f`
+            let mut visible = vec![f];
+            self.facets[f].alive = false;
+            let mut horizon = Vec::new(); // (visible facet, index of the neighbor slot)
+            let mut i = 0;
+            while i < visible.len() {
+                let v = visible[i];
+                for slot in 0..dim {
+                    let nb = self.facets[v].neighbors[slot];
+                    if !self.facets[nb].alive {
+                        continue;
+                    }
+                    if self.facets[nb].distance(apex_pt) > EPS {
+                        self.facets[nb].alive = false;
+                        visible.push(nb);
+                    } else {
+                        horizon.push((v, slot));
+                    }
+                }
+                i += 1;
+            }
+            // `horizon` may hold facets that became visible after being recorded
+            horizon.retain(|&(v, slot)| self.facets[self.facets[v].neighbors[slot]].alive);
+
+            let mut new_facets = Vec::with_capacity(horizon.len());
+            let mut ridges: HashMap<Vec<usize>, (usize, usize)> = HashMap::new();
+            for &(v, slot) in &horizon {
+                let hidden = self.facets[v].neighbors[slot];
+                let mut vertices: Vec<usize> = self.facets[v]
+                    .vertices
+                    .iter()
+                    .enumerate()
+                    .filter(|&(k, _)| k != slot)
+                    .map(|(_, &x)| x)
+                    .collect();
+                vertices.push(apex);
+                let facet = self.make_facet(vertices);
+                let nf = self.facets.len();
+                self.facets.push(facet);
+                new_facets.push(nf);
+
+                // link across the horizon ridge (opposite the apex)
+                let apex_slot = self.facets[nf]
+                    .vertices
+                    .iter()
+                    .position(|&x| x == apex)
+                    .unwrap();
+                self.facets[nf].neighbors[apex_slot] = hidden;
+                let back = self.facets[hidden]
+                    .neighbors
+                    .iter()
+                    .position(|&x| x == v)
+                    .unwrap();
+                self.facets[hidden].neighbors[back] = nf;
+
+                // link with the other new facets sharing a ridge through the apex
+                for k in 0..dim {
+                    if k == apex_slot {
+                        continue;
+                    }
+                    let mut key: Vec<usize> = self.facets[nf]
+                        .vertices
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != k)
+                        .map(|(_, &x)| x)
+                        .collect();
+                    key.sort_unstable();
+                    if let Some((other, other_slot)) = ridges.remove(&key) {
+                        self.facets[nf].neighbors[k] = other;
+                        self.facets[other].neighbors[other_slot] = nf;
+                    } else {
+                        ridges.insert(key, (nf, k));
+                    }
+                }
+            }
+
+            // hand the orphaned outside points over to the new facets
+            for &v in &visible {
+                let orphans = std::mem::take(&mut self.facets[v].outside);
+                for idx in orphans {
+                    if idx != apex {
+                        self.assign(idx, &new_facets);
+                    }
+                }
+            }
+            stack.extend(new_facets);
+        }
+        true
+    }
+}
+
+/// Computes the convex hull of a point set as a list of simplicial facets.
+///
+/// Arguments:
+///
+/// * `points`: The point set, one point per row, of shape `(npoints, dim)` with `dim >= 2`.
+///
+/// Returns:
+///
+/// An array of shape `(nfacets, dim)` whose rows hold the vertex indices of each facet.
+/// The vertices of each facet are ordered so that the facets are consistently oriented
+/// (for `dim == 3`, counter-clockwise when seen from outside). An empty array is returned
+/// if the points do not span a full-dimensional hull.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::hull::convex_hull;
+/// use ndarray::array;
+///
+/// let cube = array![
+///     [0.0, 0.0, 0.0],
+///     [1.0, 0.0, 0.0],
+///     [0.0, 1.0, 0.0],
+///     [1.0, 1.0, 0.0],
+///     [0.0, 0.0, 1.0],
+///     [1.0, 0.0, 1.0],
+///     [0.0, 1.0, 1.0],
+///     [1.0, 1.0, 1.0],
+///     [0.5, 0.5, 0.5],
+/// ];
+/// let facets = convex_hull(cube.view());
+/// assert_eq!(facets.nrows(), 12);
+/// ```
+pub fn convex_hull(points: ArrayView2<f64>) -> Array2<usize> {
+    let (npoints, dim) = points.dim();
+    assert!(dim >= 2);
+    let rows: Vec<Vec<f64>> = points.rows().into_iter().map(|r| r.to_vec()).collect();
+    if npoints <= dim {
+        return Array2::zeros((0, dim));
+    }
+    let mut qh = Quickhull {
+        points: rows.iter().map(|r| r.as_slice()).collect(),
+        interior: Vec::new(),
+        facets: Vec::new(),
+    };
+    if !qh.build() {
+        return Array2::zeros((0, dim));
+    }
+    let flat: Vec<usize> = qh
+        .facets
+        .iter()
+        .filter(|f| f.alive)
+        .flat_map(|f| f.vertices.iter().copied())
+        .collect();
+    Array2::from_shape_vec((flat.len() / dim, dim), flat).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_sphere_points;
+    use std::collections::HashSet;
+
+    fn check_hull(points: ArrayView2<f64>, facets: &Array2<usize>) {
+        let dim = points.ncols();
+        for facet in facets.rows() {
+            let pts: Vec<Vec<f64>> = facet.iter().map(|&i| points.row(i).to_vec()).collect();
+            let refs: Vec<&[f64]> = pts.iter().map(|p| p.as_slice()).collect();
+            let normal = hyperplane_normal(&refs).unwrap();
+            let offset = dot(&normal, refs[0]);
+            let sides: Vec<f64> = points
+                .rows()
+                .into_iter()
+                .map(|r| dot(&normal, r.as_slice().unwrap()) - offset)
+                .collect();
+            // every point lies on one side of each facet
+            let above = sides.iter().filter(|&&s| s > 1e-9).count();
+            let below = sides.iter().filter(|&&s| s < -1e-9).count();
+            assert!(above == 0 || below == 0);
+        }
+        // every ridge is shared by exactly two facets
+        let mut ridges: HashMap<Vec<usize>, usize> = HashMap::new();
+        for facet in facets.rows() {
+            for k in 0..dim {
+                let mut key: Vec<usize> = facet
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != k)
+                    .map(|(_, &x)| x)
+                    .collect();
+                key.sort_unstable();
+                *ridges.entry(key).or_default() += 1;
+            }
+        }
+        assert!(ridges.values().all(|&c| c == 2));
+    }
+
+    #[test]
+    fn test_hull_sphere2() {
+        let pts = random_sphere_points(2, 300, 3);
+        let facets = convex_hull(pts.view());
+        check_hull(pts.view(), &facets);
+        // Euler: a triangulated sphere with V vertices has 2V - 4 faces
+        assert_eq!(facets.nrows(), 2 * 300 - 4);
+        let verts: HashSet<usize> = facets.iter().copied().collect();
+        assert_eq!(verts.len(), 300);
+    }
+
+    #[test]
+    fn test_hull_sphere3() {
+        let pts = random_sphere_points(3, 200, 5);
+        let facets = convex_hull(pts.view());
+        check_hull(pts.view(), &facets);
+        let verts: HashSet<usize> = facets.iter().copied().collect();
+        assert_eq!(verts.len(), 200);
+    }
+
+    #[test]
+    fn test_hull_orientation() {
+        let pts = random_sphere_points(2, 50, 11);
+        let facets = convex_hull(pts.view());
+        for facet in facets.rows() {
+            let [a, b, c] = [0, 1, 2].map(|k| pts.row(facet[k]).to_vec());
+            let u: Vec<f64> = b.iter().zip(&a).map(|(x, y)| x - y).collect();
+            let v: Vec<f64> = c.iter().zip(&a).map(|(x, y)| x - y).collect();
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            assert!(dot(&cross, &a) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Quasi-Monte Carlo integration over the n-sphere.
+//!
+//! The integral of `f` over `S^n` is estimated as the mean of `f` over the first `N`
+//! points of a generator, scaled by the surface area of `S^n`. The generator must produce
+//! uniformly distributed points: `SphereN` (via [`crate::sphere_n::sphere_gen`]) does on
+//! every sphere, whereas `CylindN` is uniform on `S^2` only.
+
+use crate::cylind_n::CylindGen;
+use crate::metrics::sphere_area;
+use crate::rng::{random_rotation, SplitMix64};
+use ndarray::{Array2, ArrayView1};
+
+/// The result of a randomized quasi-Monte Carlo integration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// The estimated integral.
+    pub value: f64,
+    /// The standard error of `value`, estimated from the spread of the replicates.
+    pub std_error: f64,
+    /// The total number of function evaluations.
+    pub npoints: usize,
+}
+
+/// Averages `f` over the rows of `points`, each row being rotated by `rot` if given.
+pub(crate) fn mean_over<F>(f: &F, points: &Array2<f64>, rot: Option<&Array2<f64>>) -> f64
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let mut buf = vec![0.0; points.ncols()];
+    let total: f64 = points
+        .rows()
+        .into_iter()
+        .map(|row| match rot {
+            Some(rot) => {
+                for (b, r) in buf.iter_mut().zip(rot.rows()) {
+                    *b = r.dot(&row);
+                }
+                f(&buf)
+            }
+            None => eval(f, row),
+        })
+        .sum();
+    total / points.nrows() as f64
+}
+
+#[inline]
+fn eval<F: Fn(&[f64]) -> f64>(f: &F, row: ArrayView1<f64>) -> f64 {
+    match row.as_slice() {
+        Some(slice) => f(slice),
+        None => f(&row.to_vec()),
+    }
+}
+
+/// Estimates the integral of `f` over `S^n` from the next `npoints` points of `sgen`.
+///
+/// Arguments:
+///
+/// * `f`: The integrand, called with the coordinates of a point of `S^n`.
+/// * `sgen`: A generator of uniformly distributed points on `S^n`.
+/// * `npoints`: The number of points to use.
+///
+/// Returns:
+///
+/// The mean of `f` over the points times the surface area of `S^n`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::integrate::integrate;
+/// use sphere_n_rs::sphere_n::sphere_gen;
+/// use approx_eq::assert_approx_eq;
+///
+/// // the integral of z^2 over S^2 is 4 pi / 3
+/// let mut sgen = sphere_gen(2, &[2, 3]);
+/// let res = integrate(|x| x[2] * x[2], sgen.as_mut(), 10000);
+/// assert_approx_eq!(res, 4.0 * std::f64::consts::PI / 3.0, 1e-3);
+/// ```
+pub fn integrate<F, G>(f: F, sgen: &mut G, npoints: usize) -> f64
+where
+    F: Fn(&[f64]) -> f64,
+    G: CylindGen + ?Sized,
+{
+    assert!(npoints > 0);
+    let points = sgen.pop_batch(npoints);
+    sphere_area(points.ncols() - 1) * mean_over(&f, &points, None)
+}
+
+/// Estimates the integral of `f` over `S^n` with randomized quasi-Monte Carlo.
+///
+/// The next `npoints` points of `sgen` are randomized `nreplicates` times by independent
+/// uniformly distributed rotations. Each rotated copy is still a low-discrepancy point set
+/// and yields an unbiased estimate; their mean is returned together with its standard
+/// error.
+///
+/// Arguments:
+///
+/// * `f`: The integrand, called with the coordinates of a point of `S^n`.
+/// * `sgen`: A generator of uniformly distributed points on `S^n`.
+/// * `npoints`: The number of points per replicate.
+/// * `nreplicates`: The number of randomized replicates (at least 2).
+/// * `seed`: The seed of the random rotations.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::integrate::integrate_rqmc;
+/// use sphere_n_rs::sphere_n::sphere_gen;
+///
+/// let mut sgen = sphere_gen(4, &[2, 3, 5, 7]);
+/// let est = integrate_rqmc(|x| x[0] * x[0], sgen.as_mut(), 2000, 8, 0);
+/// let exact = 8.0 * std::f64::consts::PI.powi(2) / 15.0; // area(S^4) / 5
+/// assert!((est.value - exact).abs() < 5.0 * est.std_error + 1e-9);
+/// assert_eq!(est.npoints, 16000);
+/// ```
+pub fn integrate_rqmc<F, G>(
+    f: F,
+    sgen: &mut G,
+    npoints: usize,
+    nreplicates: usize,
+    seed: u64,
+) -> Estimate
+where
+    F: Fn(&[f64]) -> f64,
+    G: CylindGen + ?Sized,
+{
+    assert!(npoints > 0);
+    assert!(nreplicates >= 2);
+    let points = sgen.pop_batch(npoints);
+    let dim = points.ncols();
+    let mut rng = SplitMix64::new(seed);
+    let rotations: Vec<Array2<f64>> = (0..nreplicates)
+        .map(|_| random_rotation(&mut rng, dim))
+        .collect();
+    let means: Vec<f64> = rotations
+        .iter()
+        .map(|rot| mean_over(&f, &points, Some(rot)))
+        .collect();
+    let (mean, std_error) = mean_and_std_error(&means);
+    let area = sphere_area(dim - 1);
+    Estimate {
+        value: area * mean,
+        std_error: area * std_error,
+        npoints: npoints * nreplicates,
+    }
+}
+
+/// The stopping rules of [`integrate_adaptive`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveOptions {
+    /// The absolute tolerance on the standard error.
+    pub abs_tol: f64,
+    /// The tolerance on the standard error relative to the magnitude of the estimate.
+    pub rel_tol: f64,
+    /// The number of points of the first batch.
+    pub initial_points: usize,
+    /// The budget of function evaluations (points times replicates).
+    pub max_evals: usize,
+    /// The number of randomized replicates (at least 2).
+    pub nreplicates: usize,
+    /// The seed of the random rotations.
+    pub seed: u64,
+}
+
+impl Default for AdaptiveOptions {
+    fn default() -> Self {
+        AdaptiveOptions {
+            abs_tol: 1e-6,
+            rel_tol: 0.0,
+            initial_points: 128,
+            max_evals: 1 << 22,
+            nreplicates: 8,
+            seed: 0,
+        }
+    }
+}
+
+/// The outcome of [`integrate_adaptive`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntegrationReport {
+    /// The estimated integral.
+    pub value: f64,
+    /// The standard error of `value`, estimated from the spread of the replicates.
+    pub std_error: f64,
+    /// The number of points drawn from the generator.
+    pub npoints: usize,
+    /// The total number of function evaluations (points times replicates).
+    pub nevals: usize,
+    /// Whether the tolerance was met before the budget ran out.
+    pub converged: bool,
+}
+
+/// Integrates `f` over `S^n` with randomized quasi-Monte Carlo, drawing points from `sgen`
+/// in doubling batches until the requested tolerance is met or the budget is exhausted.
+///
+/// Every point is evaluated under `nreplicates` independent random rotations, fixed once at
+/// the start, so that each replicate keeps extending the same low-discrepancy sequence.
+/// After each batch the replicate means give the estimate and its standard error; the
+/// iteration stops as soon as the standard error is within
+/// `max(abs_tol, rel_tol * |estimate|)`, or when the next batch would exceed `max_evals`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::integrate::{integrate_adaptive, AdaptiveOptions};
+/// use sphere_n_rs::sphere_n::sphere_gen;
+///
+/// let mut sgen = sphere_gen(2, &[2, 3]);
+/// let options = AdaptiveOptions { abs_tol: 1e-3, ..Default::default() };
+/// let report = integrate_adaptive(|x| x[0] * x[0], sgen.as_mut(), &options);
+/// assert!(report.converged);
+/// assert!(report.std_error <= 1e-3);
+/// assert!((report.value - 4.0 * std::f64::consts::PI / 3.0).abs() < 5e-3);
+/// ```
+pub fn integrate_adaptive<F, G>(f: F, sgen: &mut G, options: &AdaptiveOptions) -> IntegrationReport
+where
+    F: Fn(&[f64]) -> f64,
+    G: CylindGen + ?Sized,
+{
+    assert!(options.initial_points > 0);
+    assert!(options.nreplicates >= 2);
+    let nrep = options.nreplicates;
+    let mut rng = SplitMix64::new(options.seed);
+    let mut rotations: Vec<Array2<f64>> = Vec::with_capacity(nrep);
+    let mut sums = vec![0.0; nrep];
+    let mut npoints = 0;
+    let mut batch = options.initial_points;
+    let mut report = IntegrationReport {
+        value: 0.0,
+        std_error: f64::INFINITY,
+        npoints: 0,
+        nevals: 0,
+        converged: false,
+    };
+    while (npoints + batch) * nrep <= options.max_evals {
+        let points = sgen.pop_batch(batch);
+        let dim = points.ncols();
+        if rotations.is_empty() {
+            rotations = (0..nrep).map(|_| random_rotation(&mut rng, dim)).collect();
+        }
+        for (sum, rot) in sums.iter_mut().zip(&rotations) {
+            *sum += batch as f64 * mean_over(&f, &points, Some(rot));
+        }
+        npoints += batch;
+        batch = npoints; // double the total
+
+        let means: Vec<f64> = sums.iter().map(|s| s / npoints as f64).collect();
+        let (mean, std_error) = mean_and_std_error(&means);
+        let area = sphere_area(dim - 1);
+        report = IntegrationReport {
+            value: area * mean,
+            std_error: area * std_error,
+            npoints,
+            nevals: npoints * nrep,
+            converged: false,
+        };
+        if report.std_error <= options.abs_tol.max(options.rel_tol * report.value.abs()) {
+            report.converged = true;
+            break;
+        }
+    }
+    report
+}
+
+/// Returns the mean of `samples` and the standard error of that mean.
+pub(crate) fn mean_and_std_error(samples: &[f64]) -> (f64, f64) {
+    let count = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / count;
+    let var = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (count - 1.0);
+    (mean, (var / count).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cylind_n::CylindN;
+    use crate::sphere_n::sphere_gen;
+    use approx_eq::assert_approx_eq;
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_integrate_constant() {
+        for n in 1..7 {
+            let mut sgen = sphere_gen(n, &PRIME_TABLE);
+            assert_approx_eq!(integrate(|_| 1.0, sgen.as_mut(), 10), sphere_area(n));
+        }
+    }
+
+    #[test]
+    fn test_integrate_moments() {
+        // the integral of x_i^2 over S^n is area(S^n) / (n + 1)
+        for n in 2..6 {
+            let mut sgen = sphere_gen(n, &PRIME_TABLE);
+            let res = integrate(|x| x[0] * x[0], sgen.as_mut(), 5000);
+            assert_approx_eq!(res, sphere_area(n) / (n + 1) as f64, 1e-2);
+        }
+        let mut cgen = CylindN::new(2, &PRIME_TABLE);
+        let res = integrate(|x| x[1].powi(4), &mut cgen, 20000);
+        assert_approx_eq!(res, 4.0 * std::f64::consts::PI / 5.0, 1e-3);
+    }
+
+    #[test]
+    fn test_integrate_rqmc() {
+        let mut sgen = sphere_gen(3, &PRIME_TABLE);
+        let f = |x: &[f64]| (x[0] + 2.0 * x[3]).exp();
+        let est = integrate_rqmc(f, sgen.as_mut(), 4000, 10, 5);
+        // integral of exp(a.x) over S^3 is 4 pi^2 I_1(|a|) / |a| with |a| = sqrt(5)
+        let a = 5.0_f64.sqrt();
+        let bessel_i1: f64 = (0..30)
+            .map(|k| {
+                let fact: f64 = (1..=k).map(|j| j as f64).product();
+                (a / 2.0).powi(2 * k + 1) / (fact * fact * (k + 1) as f64)
+            })
+            .sum();
+        let exact = 4.0 * std::f64::consts::PI.powi(2) * bessel_i1 / a;
+        assert!(est.std_error > 0.0 && est.std_error < 1e-2 * exact);
+        assert!((est.value - exact).abs() < 5.0 * est.std_error);
+    }
+
+    #[test]
+    fn test_integrate_adaptive() {
+        let f = |x: &[f64]| x[0] * x[1] + x[2] * x[2];
+        let exact = sphere_area(2) / 3.0;
+        let options = AdaptiveOptions {
+            abs_tol: 1e-3,
+            ..Default::default()
+        };
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let report = integrate_adaptive(f, sgen.as_mut(), &options);
+        assert!(report.converged);
+        assert!(report.std_error <= 1e-3);
+        assert!((report.value - exact).abs() < 5e-3);
+        assert!(report.npoints.is_power_of_two());
+        assert_eq!(report.nevals, report.npoints * options.nreplicates);
+
+        // a tighter tolerance needs more points
+        let options = AdaptiveOptions {
+            abs_tol: 3e-4,
+            ..options
+        };
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let tight = integrate_adaptive(f, sgen.as_mut(), &options);
+        assert!(tight.npoints > report.npoints);
+    }
+
+    #[test]
+    fn test_integrate_adaptive_budget() {
+        let options = AdaptiveOptions {
+            abs_tol: 0.0,
+            initial_points: 100,
+            max_evals: 10_000,
+            nreplicates: 4,
+            ..Default::default()
+        };
+        let mut sgen = sphere_gen(2, &PRIME_TABLE);
+        let report = integrate_adaptive(|x| x[2].abs(), sgen.as_mut(), &options);
+        assert!(!report.converged);
+        // batches of 100, 100, 200, 400, 800 points; the next one would exceed the budget
+        assert_eq!(report.npoints, 1600);
+        assert_eq!(report.nevals, 6400);
+        assert_approx_eq!(report.value, 2.0 * std::f64::consts::PI, 1e-2);
+    }
+}
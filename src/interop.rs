@@ -0,0 +1,161 @@
+//! Conversions of the generated points into `nalgebra` and `glam` types.
+//!
+//! Each library is behind the feature of the same name. Points on `S^2` become unit
+//! 3-vectors and points on `S^3` become unit quaternions, with the last coordinate as the
+//! real part `w` (the `cos` term of `Sphere3`). The points are renormalized on conversion, so
+//! the results are unit length to the precision of the target type.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "nalgebra")]
+//! # {
+//! use sphere_n_rs::interop::PopTyped;
+//! use sphere_n_rs::sphere_n::sphere_gen;
+//!
+//! let mut sgen = sphere_gen(2, &[2, 3]);
+//! let v = sgen.pop_unit_vector3();
+//! assert!((v.norm() - 1.0).abs() < 1e-12);
+//! # }
+//! ```
+
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::Sphere3;
+
+/// Typed `pop` methods for any generator of points on `S^2` or `S^3`.
+///
+/// The methods panic if the points of the generator do not have the expected number of
+/// coordinates (3 for vectors, 4 for quaternions).
+pub trait PopTyped: CylindGen {
+    /// Generates the next point on `S^2` as a `nalgebra` unit vector.
+    #[cfg(feature = "nalgebra")]
+    fn pop_unit_vector3(&mut self) -> nalgebra::Unit<nalgebra::Vector3<f64>> {
+        to_unit_vector3(&self.pop_vec())
+    }
+
+    /// Generates the next point on `S^3` as a `nalgebra` unit quaternion.
+    #[cfg(feature = "nalgebra")]
+    fn pop_unit_quaternion(&mut self) -> nalgebra::UnitQuaternion<f64> {
+        to_unit_quaternion(&self.pop_vec())
+    }
+
+    /// Generates the next point on `S^2` as a `glam` vector.
+    #[cfg(feature = "glam")]
+    fn pop_vec3(&mut self) -> glam::Vec3 {
+        to_vec3(&self.pop_vec())
+    }
+
+    /// Generates the next point on `S^3` as a `glam` quaternion.
+    #[cfg(feature = "glam")]
+    fn pop_quat(&mut self) -> glam::Quat {
+        to_quat(&self.pop_vec())
+    }
+}
+
+impl<G: CylindGen + ?Sized> PopTyped for G {}
+
+#[cfg(feature = "nalgebra")]
+impl Sphere3 {
+    /// Generates the next point as a `nalgebra` unit quaternion, a uniformly distributed
+    /// rotation.
+    pub fn pop_unit_quaternion(&mut self) -> nalgebra::UnitQuaternion<f64> {
+        to_unit_quaternion(&self.pop())
+    }
+}
+
+#[cfg(feature = "glam")]
+impl Sphere3 {
+    /// Generates the next point as a `glam` quaternion, a uniformly distributed rotation.
+    pub fn pop_quat(&mut self) -> glam::Quat {
+        to_quat(&self.pop())
+    }
+}
+
+/// Converts a point on `S^2` into a `nalgebra` unit vector.
+#[cfg(feature = "nalgebra")]
+pub fn to_unit_vector3(point: &[f64]) -> nalgebra::Unit<nalgebra::Vector3<f64>> {
+    let [x, y, z] = coords::<3>(point);
+    nalgebra::Unit::new_normalize(nalgebra::Vector3::new(x, y, z))
+}
+
+/// Converts a point on `S^3` into a `nalgebra` unit quaternion, with `point[3]` as `w`.
+#[cfg(feature = "nalgebra")]
+pub fn to_unit_quaternion(point: &[f64]) -> nalgebra::UnitQuaternion<f64> {
+    let [i, j, k, w] = coords::<4>(point);
+    nalgebra::UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(w, i, j, k))
+}
+
+/// Converts a point on `S^2` into a `glam` unit vector.
+#[cfg(feature = "glam")]
+pub fn to_vec3(point: &[f64]) -> glam::Vec3 {
+    let [x, y, z] = coords::<3>(point);
+    glam::DVec3::new(x, y, z).normalize().as_vec3()
+}
+
+/// Converts a point on `S^3` into a `glam` unit quaternion, with `point[3]` as `w`.
+#[cfg(feature = "glam")]
+pub fn to_quat(point: &[f64]) -> glam::Quat {
+    let [x, y, z, w] = coords::<4>(point);
+    glam::DQuat::from_xyzw(x, y, z, w).normalize().as_quat()
+}
+
+fn coords<const N: usize>(point: &[f64]) -> [f64; N] {
+    point.try_into().unwrap_or_else(|_| {
+        panic!(
+            "expected a point with {} coordinates, found {}",
+            N,
+            point.len()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_nalgebra() {
+        use crate::sphere_n::{sphere_gen, SphereGen};
+        use approx_eq::assert_approx_eq;
+
+        let mut sgen = Sphere3::new(&[2, 3, 5]);
+        let point = sgen.pop();
+        sgen.reseed(0);
+        let q = sgen.pop_unit_quaternion();
+        assert_approx_eq!(q.w, point[3]);
+        assert_approx_eq!(q.i, point[0]);
+        assert_approx_eq!(q.norm(), 1.0);
+
+        let mut s3 = sphere_gen(3, &[2, 3, 5]);
+        assert_approx_eq!(s3.pop_unit_quaternion().k, point[2]);
+        let mut s2 = crate::CylindN::new(2, &[2, 3]);
+        let v = s2.pop_unit_vector3();
+        assert_approx_eq!(v.x, -0.5);
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn test_glam() {
+        use crate::sphere_n::{sphere_gen, SphereGen};
+
+        let mut sgen = Sphere3::new(&[2, 3, 5]);
+        let point = sgen.pop();
+        sgen.reseed(0);
+        let q = sgen.pop_quat();
+        assert!(q.is_normalized());
+        assert!((q.w as f64 - point[3]).abs() < 1e-6);
+
+        let mut s2 = sphere_gen(2, &[2, 3]);
+        for _ in 0..20 {
+            assert!(s2.pop_vec3().is_normalized());
+        }
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    #[should_panic]
+    fn test_wrong_dimension() {
+        to_quat(&[1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::cylind_n::CylindGen;
+use crate::npy::Dtype;
+use ndarray::Array2;
+use std::io::{self, BufRead, Write};
+
+/// The output format of a `PointWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointFormat {
+    /// Comma-separated values, one point per line.
+    Csv,
+    /// Space-separated values, one point per line.
+    Txt,
+    /// JSON Lines, one JSON array per point.
+    JsonLines,
+    /// Raw little-endian floats, the coordinates of the points one after the other.
+    Binary,
+}
+
+/// Writes points one at a time to any `io::Write`, so that large point sets can be streamed
+/// from a generator without being stored in memory.
+///
+/// Text output uses the shortest representation that reads back to the same value, unless a
+/// fixed number of decimals is set with `precision`. With `header`, text formats start with a
+/// row naming the coordinates `x0, x1, ...` (a JSON array of strings for JSON Lines); raw
+/// binary output has no header and is written as `f64` unless another `dtype` is set.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::io::{PointFormat, PointWriter};
+/// use sphere_n_rs::CylindN;
+///
+/// let mut cgen = CylindN::new(2, &[2, 3]);
+/// let mut writer = PointWriter::new(Vec::new(), PointFormat::JsonLines).precision(3);
+/// writer.write_from(&mut cgen, 2).unwrap();
+/// let text = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+/// assert_eq!(text.lines().next().unwrap(), "[-0.500,0.866,0.000]");
+/// ```
+pub struct PointWriter<W: Write> {
+    writer: W,
+    format: PointFormat,
+    precision: Option<usize>,
+    header: bool,
+    dtype: Dtype,
+    started: bool,
+}
+
+impl<W: Write> PointWriter<W> {
+    /// Creates a writer of the given format, without header and with full precision.
+    pub fn new(writer: W, format: PointFormat) -> Self {
+        PointWriter {
+            writer,
+            format,
+            precision: None,
+            header: false,
+            dtype: Dtype::F64,
+            started: false,
+        }
+    }
+
+    /// Writes text values with the given number of decimals.
+    pub fn precision(mut self, digits: usize) -> Self {
+        self.precision = Some(digits);
+        self
+    }
+
+    /// Starts text output with a header row.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Sets the float type of raw binary output.
+    pub fn dtype(mut self, dtype: Dtype) -> Self {
+        self.dtype = dtype;
+        self
+    }
+
+    /// Writes a single point.
+    pub fn write_point(&mut self, point: &[f64]) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            if self.header {
+                self.write_header(point.len())?;
+            }
+        }
+        let sep = match self.format {
+            PointFormat::Binary => {
+                for &x in point {
+                    match self.dtype {
+                        Dtype::F32 => self.writer.write_all(&(x as f32).to_le_bytes())?,
+                        Dtype::F64 => self.writer.write_all(&x.to_le_bytes())?,
+                    }
+                }
+                return Ok(());
+            }
+            PointFormat::Csv | PointFormat::JsonLines => ",",
+            PointFormat::Txt => " ",
+        };
+        let json = self.format == PointFormat::JsonLines;
+        if json {
+            self.writer.write_all(b"[")?;
+        }
+        for (i, &x) in point.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_all(sep.as_bytes())?;
+            }
+            match self.precision {
+                // JSON has no representation of NaN and infinities
+                _ if json && !x.is_finite() => write!(self.writer, "null")?,
+                Some(digits) => write!(self.writer, "{:.*}", digits, x)?,
+                None => write!(self.writer, "{}", x)?,
+            }
+        }
+        if json {
+            self.writer.write_all(b"]")?;
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    /// Writes the next `npoints` points of a generator.
+    pub fn write_from<G: CylindGen + ?Sized>(
+        &mut self,
+        sgen: &mut G,
+        npoints: usize,
+    ) -> io::Result<()> {
+        for _ in 0..npoints {
+            self.write_point(&sgen.pop_vec())?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the output and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_header(&mut self, dim: usize) -> io::Result<()> {
+        let names: Vec<String> = (0..dim).map(|i| format!("x{}", i)).collect();
+        match self.format {
+            PointFormat::Binary => Ok(()),
+            PointFormat::Csv => writeln!(self.writer, "{}", names.join(",")),
+            PointFormat::Txt => writeln!(self.writer, "{}", names.join(" ")),
+            PointFormat::JsonLines => {
+                let quoted: Vec<String> = names.iter().map(|n| format!("\"{}\"", n)).collect();
+                writeln!(self.writer, "[{}]", quoted.join(","))
+            }
+        }
+    }
+}
+
+/// Reads a point set from delimited text, one point per line.
+///
+/// The coordinates may be separated by commas and/or whitespace, so both the CSV and the
+/// plain-text output of the `sphere-n` tool are accepted. Blank lines and lines starting with
+/// `#` are skipped, as is a first line in which no field parses as a number (a header).
+///
+/// Returns:
+///
+/// An array with one point per row, or an `InvalidData` error if a line does not parse or
+/// the lines have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::io::read_csv;
+///
+/// let text = "x,y\n0.6,0.8\n# comment\n1,0\n";
+/// let pts = read_csv(text.as_bytes()).unwrap();
+/// assert_eq!(pts.dim(), (2, 2));
+/// assert_eq!(pts[[0, 1]], 0.8);
+/// ```
+pub fn read_csv<R: BufRead>(reader: R) -> io::Result<Array2<f64>> {
+    let mut flat = Vec::new();
+    let mut dim = None;
+    let mut npoints = 0;
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let row: Result<Vec<f64>, _> = fields.iter().map(|s| s.parse::<f64>()).collect();
+        let row = match row {
+            Ok(row) => row,
+            // a header names every column; a partly numeric first line is a bad data row
+            Err(_)
+                if npoints == 0
+                    && dim.is_none()
+                    && fields.iter().all(|s| s.parse::<f64>().is_err()) =>
+            {
+                dim = Some(0);
+                continue;
+            }
+            Err(e) => return Err(invalid_data(format!("line {}: {}", lineno + 1, e))),
+        };
+        match dim {
+            Some(d) if d != 0 && d != row.len() => {
+                return Err(invalid_data(format!(
+                    "line {}: expected {} values, found {}",
+                    lineno + 1,
+                    d,
+                    row.len()
+                )))
+            }
+            _ => dim = Some(row.len()),
+        }
+        flat.extend(row);
+        npoints += 1;
+    }
+    Array2::from_shape_vec((npoints, dim.unwrap_or(0)), flat)
+        .map_err(|e| invalid_data(e.to_string()))
+}
+
+pub(crate) fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere_n::sphere_gen;
+
+    #[test]
+    fn test_read_csv_whitespace() {
+        let pts = read_csv("1 0 0\n\n0 0.5  -0.5\n".as_bytes()).unwrap();
+        assert_eq!(pts.dim(), (2, 3));
+        assert_eq!(pts[[1, 2]], -0.5);
+    }
+
+    #[test]
+    fn test_read_csv_errors() {
+        assert!(read_csv("1,0\n1,0,0\n".as_bytes()).is_err());
+        assert!(read_csv("1,0\nx,y\n".as_bytes()).is_err());
+        // a malformed first row is an error, not a header
+        let err = read_csv("1,0,abc\n1,0,0\n".as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn test_point_writer_csv_round_trip() {
+        let mut sgen = sphere_gen(3, &[2, 3, 5]);
+        let mut writer = PointWriter::new(Vec::new(), PointFormat::Csv).header(true);
+        writer.write_from(sgen.as_mut(), 20).unwrap();
+        let buf = writer.into_inner().unwrap();
+        assert!(buf.starts_with(b"x0,x1,x2,x3\n"));
+        sgen.reseed(0);
+        assert_eq!(read_csv(&buf[..]).unwrap(), sgen.pop_batch(20));
+    }
+
+    #[test]
+    fn test_point_writer_binary() {
+        let mut writer = PointWriter::new(Vec::new(), PointFormat::Binary)
+            .header(true)
+            .dtype(Dtype::F32);
+        writer.write_point(&[0.5, -1.0]).unwrap();
+        let buf = writer.into_inner().unwrap();
+        assert_eq!(buf.len(), 8);
+        assert_eq!(&buf[4..], &(-1.0_f32).to_le_bytes());
+    }
+}
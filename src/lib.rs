@@ -1,5 +1,30 @@
+pub mod builder;
 pub mod cylind_n;
+#[cfg(feature = "rand")]
+pub mod distribution;
+pub mod ellipsoid;
+pub mod experiment;
+pub mod harmonics;
+pub mod hull;
+pub mod integrate;
+#[cfg(any(feature = "nalgebra", feature = "glam"))]
+pub mod interop;
+pub mod io;
+pub mod mesh;
+pub mod metrics;
+pub mod npy;
+pub mod product;
+#[cfg(feature = "python")]
+mod python;
+pub mod render;
+pub mod rng;
+pub mod shell;
+pub mod simplex;
 pub mod sphere_n;
+pub mod state;
+pub mod stiefel;
+pub mod vmf;
+pub mod zonal;
 
 pub use crate::cylind_n::{CylindGen, CylindN};
 pub use crate::sphere_n::{Sphere3, SphereGen, SphereN};
@@ -126,4 +151,15 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_pop_batch_empty() {
+        assert_eq!(SphereN::new(4, &PRIME_TABLE).pop_batch(0).shape(), [0, 6]);
+        assert_eq!(CylindN::new(2, &PRIME_TABLE).pop_batch(0).shape(), [0, 3]);
+        for n in 1..6 {
+            let mut sgen = sphere_gen(n, &PRIME_TABLE);
+            assert_eq!(sgen.pop_batch(0).shape(), [0, n + 1]);
+            assert_eq!(sgen.pop_vec().len(), sgen.dim());
+        }
+    }
 }
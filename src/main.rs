@@ -0,0 +1,507 @@
+//! Command-line tool writing low-discrepancy point sets on spheres.
+//!
+//! ```text
+//! sphere-n --dim 3 --method sphere --count 1000 --seed 0 --format csv --out points.csv
+//! sphere-n metrics points.csv
+//! ```
+//!
+//! Every option is optional; see `sphere-n --help` for the defaults.
+
+use ndarray::{Array2, Axis};
+use sphere_n_rs::builder::{CylindBuilder, SphereBuilder};
+use sphere_n_rs::io::{read_csv, PointFormat, PointWriter};
+use sphere_n_rs::mesh::SphereMesh;
+use sphere_n_rs::metrics::{cap_discrepancy, covering_radius, min_separation, riesz_energy};
+use sphere_n_rs::npy::{read_npy, write_npy, Dtype};
+use sphere_n_rs::{CylindGen, PRIME_TABLE};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Usage: sphere-n [generate] [OPTIONS]
+       sphere-n metrics <FILE> [METRICS OPTIONS]
+
+Writes low-discrepancy points on the n-sphere S^n, one point of n + 1 coordinates per line,
+or reports quality metrics of a point set read from a CSV/text or .npy file (`-` for stdin).
+
+Options:
+  --dim <N>          dimension n of the sphere S^n [default: 2]
+  --method <METHOD>  sphere, cylind or sphere3 [default: sphere]
+  --count <COUNT>    number of points [default: 100]
+  --bases <LIST>     comma-separated bases, n of them are used [default: 2,3,5,7,...]
+  --seed <SEED>      index offset of the sequences (reseed) [default: 0]
+  --format <FORMAT>  csv, txt (space-separated), jsonl (JSON Lines), bin (raw
+                     little-endian floats), npy, or ply/obj (triangle mesh, S^2 only)
+                     [default: csv]
+  --dtype <DTYPE>    f64 or f32, for bin and npy output [default: f64]
+  --precision <N>    number of decimals of text output [default: shortest exact]
+  --header           start text output with a header row
+  --out <FILE>       output file [default: stdout]
+  -h, --help         print this help
+
+Metrics options:
+  --format <FORMAT>  csv or npy [default: npy for *.npy files, csv otherwise]
+  --ncaps <N>        number of random caps of the cap discrepancy [default: 1000]
+  --nprobes <N>      number of random probes of the covering radius [default: 10000]
+  --seed <SEED>      seed of the random caps and probes [default: 0]
+";
+
+/// The point-set construction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// The recursive `SphereN` construction (with `Sphere3` and the `lds` generators for
+    /// low dimensions).
+    Sphere,
+    /// The cylindrical `CylindN` construction.
+    Cylind,
+    /// The `Sphere3` construction, for `S^3` only.
+    Sphere3,
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sphere" => Ok(Method::Sphere),
+            "cylind" => Ok(Method::Cylind),
+            "sphere3" => Ok(Method::Sphere3),
+            _ => Err(format!("unknown method `{}`", s)),
+        }
+    }
+}
+
+/// The output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text(PointFormat),
+    Npy,
+    Ply,
+    Obj,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Text(PointFormat::Csv)),
+            "txt" => Ok(Format::Text(PointFormat::Txt)),
+            "jsonl" => Ok(Format::Text(PointFormat::JsonLines)),
+            "bin" => Ok(Format::Text(PointFormat::Binary)),
+            "npy" => Ok(Format::Npy),
+            "ply" => Ok(Format::Ply),
+            "obj" => Ok(Format::Obj),
+            _ => Err(format!("unknown format `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    dim: usize,
+    method: Method,
+    count: usize,
+    bases: Vec<usize>,
+    seed: usize,
+    format: Format,
+    dtype: Dtype,
+    precision: Option<usize>,
+    header: bool,
+    out: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            dim: 2,
+            method: Method::Sphere,
+            count: 100,
+            bases: PRIME_TABLE.to_vec(),
+            seed: 0,
+            format: Format::Text(PointFormat::Csv),
+            dtype: Dtype::F64,
+            precision: None,
+            header: false,
+            out: None,
+        }
+    }
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, String>
+where
+    T::Err: ToString,
+{
+    value
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|e: T::Err| e.to_string()))
+        .collect()
+}
+
+/// Parses the command-line arguments; returns `None` if the help was requested.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, Box<dyn Error>> {
+    let mut options = Options::default();
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            return Ok(None);
+        }
+        if flag == "--header" {
+            options.header = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", flag))?;
+        match flag.as_str() {
+            "--dim" => options.dim = value.parse()?,
+            "--method" => options.method = value.parse()?,
+            "--count" => options.count = value.parse()?,
+            "--bases" => options.bases = parse_list(&value)?,
+            "--seed" => options.seed = value.parse()?,
+            "--format" => options.format = value.parse()?,
+            "--dtype" => {
+                options.dtype = match value.as_str() {
+                    "f64" => Dtype::F64,
+                    "f32" => Dtype::F32,
+                    _ => return Err(format!("unknown dtype `{}`", value).into()),
+                }
+            }
+            "--precision" => options.precision = Some(value.parse()?),
+            "--out" => options.out = Some(value),
+            _ => return Err(format!("unknown option `{}`", flag).into()),
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Builds the generator of `S^n` for the given options.
+fn make_gen(options: &Options) -> Result<Box<dyn CylindGen>, String> {
+    let n = options.dim;
+    if options.bases.len() < n {
+        return Err(format!(
+            "{} bases are needed for S^{}, {} given",
+            n,
+            n,
+            options.bases.len()
+        ));
+    }
+    let base = &options.bases;
+    match options.method {
+        Method::Sphere if n >= 1 => SphereBuilder::new(n).base(base).build(),
+        Method::Cylind if n >= 2 => {
+            Ok(Box::new(CylindBuilder::new(n).base(base).build_cylind_n()?))
+        }
+        Method::Sphere3 if n == 3 => SphereBuilder::new(3).base(base).build(),
+        Method::Sphere => Err("the `sphere` method needs a dimension of at least 1".into()),
+        Method::Cylind => Err("the `cylind` method needs a dimension of at least 2".into()),
+        Method::Sphere3 => Err("the `sphere3` method needs a dimension of 3".into()),
+    }
+}
+
+fn write_points<W: Write>(
+    sgen: &mut dyn CylindGen,
+    options: &Options,
+    mut out: W,
+) -> io::Result<()> {
+    match options.format {
+        Format::Text(format) => {
+            let mut writer = PointWriter::new(out, format)
+                .header(options.header)
+                .dtype(options.dtype);
+            if let Some(digits) = options.precision {
+                writer = writer.precision(digits);
+            }
+            writer.write_from(sgen, options.count)?;
+            writer.into_inner()?;
+            Ok(())
+        }
+        Format::Npy => {
+            let points = sgen.pop_batch(options.count);
+            write_npy(points.view(), options.dtype, &mut out)?;
+            out.flush()
+        }
+        Format::Ply | Format::Obj => {
+            let points = sgen.pop_batch(options.count);
+            if points.ncols() != 3 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "mesh output is only available for S^2",
+                ));
+            }
+            let mesh = SphereMesh::from_points(points.view());
+            if options.format == Format::Ply {
+                mesh.write_ply(out)
+            } else {
+                mesh.write_obj(out)
+            }
+        }
+    }
+}
+
+/// The options of the `metrics` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+struct MetricsOptions {
+    path: String,
+    npy: bool,
+    ncaps: usize,
+    nprobes: usize,
+    seed: u64,
+}
+
+fn parse_metrics_args<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<Option<MetricsOptions>, Box<dyn Error>> {
+    let path = match args.next() {
+        Some(flag) if flag == "-h" || flag == "--help" => return Ok(None),
+        Some(path) => path,
+        None => return Err("missing input file for `metrics`".into()),
+    };
+    let mut options = MetricsOptions {
+        npy: path.ends_with(".npy"),
+        path,
+        ncaps: 1000,
+        nprobes: 10000,
+        seed: 0,
+    };
+    while let Some(flag) = args.next() {
+        if flag == "-h" || flag == "--help" {
+            return Ok(None);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for `{}`", flag))?;
+        match flag.as_str() {
+            "--format" => {
+                options.npy = match value.as_str() {
+                    "npy" => true,
+                    "csv" => false,
+                    _ => return Err(format!("unknown format `{}`", value).into()),
+                }
+            }
+            "--ncaps" => options.ncaps = value.parse()?,
+            "--nprobes" => options.nprobes = value.parse()?,
+            "--seed" => options.seed = value.parse()?,
+            _ => return Err(format!("unknown option `{}`", flag).into()),
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Writes the quality metrics of a point set, one `name: value` line each.
+///
+/// The norm error is the largest deviation of the norms from 1; the other metrics are
+/// computed on the normalized points. The energy is the Riesz `(n-1)`-energy of `S^n`
+/// divided by `N^2`, as in the `experiment` module.
+fn write_metrics<W: Write>(
+    points: &Array2<f64>,
+    options: &MetricsOptions,
+    mut out: W,
+) -> Result<(), Box<dyn Error>> {
+    let (npoints, dim) = points.dim();
+    if npoints < 2 || dim < 2 {
+        return Err("the point set needs at least 2 points of 2 coordinates".into());
+    }
+    let norms = points.map_axis(Axis(1), |row| row.dot(&row).sqrt());
+    // a zero (or non-finite) row has no direction and would turn every metric into NaN
+    if let Some(i) = norms.iter().position(|&r| !(r.is_finite() && r > 0.0)) {
+        return Err(format!(
+            "point {} has norm {} and cannot be normalized",
+            i + 1,
+            norms[i]
+        )
+        .into());
+    }
+    let norm_error = norms
+        .iter()
+        .fold(0.0_f64, |acc, r| acc.max((r - 1.0).abs()));
+    let unit = points / &norms.insert_axis(Axis(1));
+    let view = unit.view();
+    let n = dim - 1;
+    writeln!(out, "points: {}", npoints)?;
+    writeln!(out, "sphere: S^{}", n)?;
+    writeln!(out, "norm_error: {:e}", norm_error)?;
+    writeln!(out, "min_separation: {}", min_separation(view))?;
+    writeln!(
+        out,
+        "covering_radius: {}",
+        covering_radius(view, options.nprobes, options.seed)
+    )?;
+    writeln!(
+        out,
+        "cap_discrepancy: {}",
+        cap_discrepancy(view, options.ncaps, options.seed)
+    )?;
+    writeln!(
+        out,
+        "energy: {}",
+        riesz_energy(view, (n - 1) as f64) / (npoints * npoints) as f64
+    )?;
+    Ok(())
+}
+
+fn run_metrics(options: &MetricsOptions) -> Result<(), Box<dyn Error>> {
+    let input: Box<dyn io::BufRead> = if options.path == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(&options.path)?))
+    };
+    let points = if options.npy {
+        read_npy(input)?
+    } else {
+        read_csv(input)?
+    };
+    write_metrics(&points, options, io::stdout().lock())
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1).peekable();
+    match args.peek().map(String::as_str) {
+        Some("metrics") => {
+            args.next();
+            return match parse_metrics_args(args)? {
+                Some(options) => run_metrics(&options),
+                None => {
+                    print!("{}", USAGE);
+                    Ok(())
+                }
+            };
+        }
+        Some("generate") => {
+            args.next();
+        }
+        _ => {}
+    }
+    let options = match parse_args(args)? {
+        Some(options) => options,
+        None => {
+            print!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let mut sgen = make_gen(&options)?;
+    sgen.reseed(options.seed);
+    let out: Box<dyn Write> = match &options.out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    match write_points(sgen.as_mut(), &options, out) {
+        // the reader went away, e.g. `sphere-n ... | head`
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        res => Ok(res?),
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("sphere-n: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sphere_n_rs::sphere_n::sphere_gen;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(args("--dim 4 --method cylind --bases 3,5,7,11 --seed 9"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.dim, 4);
+        assert_eq!(options.method, Method::Cylind);
+        assert_eq!(options.bases, vec![3, 5, 7, 11]);
+        assert_eq!(options.seed, 9);
+        assert_eq!(options.count, 100);
+        assert!(parse_args(args("--help")).unwrap().is_none());
+        assert!(parse_args(args("--method cube")).is_err());
+        assert!(parse_args(args("--dim")).is_err());
+    }
+
+    #[test]
+    fn test_write_points() {
+        let options = parse_args(args("--dim 3 --count 5 --format txt --header"))
+            .unwrap()
+            .unwrap();
+        let mut sgen = make_gen(&options).unwrap();
+        let mut buf = Vec::new();
+        write_points(sgen.as_mut(), &options, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert_eq!(text.lines().count(), 6);
+        for line in text.lines().skip(1) {
+            let norm: f64 = line
+                .split(' ')
+                .map(|x| x.parse::<f64>().unwrap().powi(2))
+                .sum();
+            assert!((norm - 1.0).abs() < 1e-12);
+        }
+        let bad = Options {
+            dim: 2,
+            method: Method::Sphere3,
+            ..options
+        };
+        assert!(make_gen(&bad).is_err());
+
+        let options = parse_args(args("--dim 4 --count 7 --format npy --dtype f32"))
+            .unwrap()
+            .unwrap();
+        let mut buf = Vec::new();
+        write_points(make_gen(&options).unwrap().as_mut(), &options, &mut buf).unwrap();
+        assert_eq!(read_npy(&buf[..]).unwrap().dim(), (7, 5));
+    }
+
+    #[test]
+    fn test_make_gen_bases() {
+        for line in [
+            "--dim 2 --bases 1,3",
+            "--dim 2 --bases 0,3",
+            "--dim 2 --bases 4,6",
+            "--dim 3 --method cylind --bases 2,1,5",
+            "--dim 3 --method sphere3 --bases 2,9,3",
+            "--dim 5 --bases 2,3,5,0,7",
+        ] {
+            let options = parse_args(args(line)).unwrap().unwrap();
+            assert!(make_gen(&options).is_err(), "{}", line);
+        }
+        let options = parse_args(args("--dim 2 --bases 4,9")).unwrap().unwrap();
+        assert!(make_gen(&options).is_ok());
+    }
+
+    #[test]
+    fn test_metrics() {
+        let options = parse_metrics_args(args("pts.npy --ncaps 50 --nprobes 100"))
+            .unwrap()
+            .unwrap();
+        assert!(options.npy);
+        assert_eq!(options.ncaps, 50);
+        let points = sphere_gen(2, &PRIME_TABLE).pop_batch(64);
+        let mut buf = Vec::new();
+        write_metrics(&points, &options, &mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let names: Vec<&str> = text.lines().map(|l| l.split(':').next().unwrap()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "points",
+                "sphere",
+                "norm_error",
+                "min_separation",
+                "covering_radius",
+                "cap_discrepancy",
+                "energy"
+            ]
+        );
+        assert!(parse_metrics_args(args("")).is_err());
+
+        let mut points = points;
+        points.row_mut(5).fill(0.0);
+        let err = write_metrics(&points, &options, Vec::new()).unwrap_err();
+        assert!(err.to_string().starts_with("point 6 has norm 0"));
+    }
+}
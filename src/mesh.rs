@@ -0,0 +1,134 @@
+use crate::hull::convex_hull;
+use ndarray::{Array2, ArrayView2, Axis};
+use std::io::{self, Write};
+
+/// A triangle mesh of a point set on `S^2`.
+///
+/// For points on the sphere, the faces of their convex hull are exactly the triangles of the
+/// spherical Delaunay triangulation, so the mesh is obtained from `convex_hull`. The faces
+/// are oriented counter-clockwise when seen from outside, and the vertex normals are the
+/// (normalized) points themselves.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::mesh::SphereMesh;
+/// use sphere_n_rs::CylindGen;
+/// use lds_rs::lds::Sphere;
+///
+/// let points = Sphere::new(&[2, 3]).pop_batch(100);
+/// let mesh = SphereMesh::from_points(points.view());
+/// // Euler's formula for a triangulated sphere: F = 2V - 4
+/// assert_eq!(mesh.triangles.nrows(), 196);
+/// let mut obj = Vec::new();
+/// mesh.write_obj(&mut obj).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphereMesh {
+    /// The vertices, one point per row.
+    pub vertices: Array2<f64>,
+    /// The unit vertex normals, one per row.
+    pub normals: Array2<f64>,
+    /// The vertex indices of the triangles, one triangle per row.
+    pub triangles: Array2<usize>,
+}
+
+impl SphereMesh {
+    /// Triangulates a point set on `S^2`, given with one point of 3 coordinates per row.
+    pub fn from_points(points: ArrayView2<f64>) -> Self {
+        assert_eq!(points.ncols(), 3, "the points must lie on S^2");
+        let norms = points.map_axis(Axis(1), |row| row.dot(&row).sqrt());
+        SphereMesh {
+            vertices: points.to_owned(),
+            normals: &points / &norms.insert_axis(Axis(1)),
+            triangles: convex_hull(points),
+        }
+    }
+
+    /// Writes the mesh in the ASCII PLY format, with per-vertex normals.
+    pub fn write_ply<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format ascii 1.0")?;
+        writeln!(writer, "comment generated by sphere-n-rs")?;
+        writeln!(writer, "element vertex {}", self.vertices.nrows())?;
+        for name in ["x", "y", "z", "nx", "ny", "nz"] {
+            writeln!(writer, "property double {}", name)?;
+        }
+        writeln!(writer, "element face {}", self.triangles.nrows())?;
+        writeln!(writer, "property list uchar int vertex_indices")?;
+        writeln!(writer, "end_header")?;
+        for (v, n) in self.vertices.rows().into_iter().zip(self.normals.rows()) {
+            writeln!(
+                writer,
+                "{} {} {} {} {} {}",
+                v[0], v[1], v[2], n[0], n[1], n[2]
+            )?;
+        }
+        for t in self.triangles.rows() {
+            writeln!(writer, "3 {} {} {}", t[0], t[1], t[2])?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the mesh in the Wavefront OBJ format, with per-vertex normals.
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "# generated by sphere-n-rs")?;
+        for v in self.vertices.rows() {
+            writeln!(writer, "v {} {} {}", v[0], v[1], v[2])?;
+        }
+        for n in self.normals.rows() {
+            writeln!(writer, "vn {} {} {}", n[0], n[1], n[2])?;
+        }
+        // OBJ indices start at 1
+        for t in self.triangles.rows() {
+            let (a, b, c) = (t[0] + 1, t[1] + 1, t[2] + 1);
+            writeln!(writer, "f {}//{} {}//{} {}//{}", a, a, b, b, c, c)?;
+        }
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cylind_n::CylindGen;
+    use crate::CylindN;
+
+    #[test]
+    fn test_mesh_orientation() {
+        let points = CylindN::new(2, &[2, 3]).pop_batch(60);
+        let mesh = SphereMesh::from_points(points.view());
+        assert_eq!(mesh.triangles.nrows(), 2 * 60 - 4);
+        // outward faces: the normal of each triangle points away from the origin
+        for t in mesh.triangles.rows() {
+            let (a, b, c) = (
+                mesh.vertices.row(t[0]),
+                mesh.vertices.row(t[1]),
+                mesh.vertices.row(t[2]),
+            );
+            let (u, v) = (&b - &a, &c - &a);
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            assert!(cross[0] * a[0] + cross[1] * a[1] + cross[2] * a[2] > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_mesh_writers() {
+        let points = CylindN::new(2, &[2, 3]).pop_batch(10);
+        let mesh = SphereMesh::from_points(points.view());
+        let mut ply = Vec::new();
+        mesh.write_ply(&mut ply).unwrap();
+        let ply = String::from_utf8(ply).unwrap();
+        assert!(ply.contains("element vertex 10\n") && ply.contains("element face 16\n"));
+        assert_eq!(ply.lines().count(), 13 + 10 + 16);
+        let mut obj = Vec::new();
+        mesh.write_obj(&mut obj).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 16);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("vn ")).count(), 10);
+    }
+}
@@ -0,0 +1,549 @@
+//! Quality metrics for point sets on the n-sphere.
+//!
+//! Point sets are passed as two-dimensional arrays with one point per row, i.e. an
+//! array of shape `(npoints, n + 1)` holds points on `S^n`.
+
+use crate::hull::convex_hull;
+use crate::rng::SplitMix64;
+use crate::sphere_n::sine_power_cdf;
+use ndarray::ArrayView2;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Above this ambient dimension the `3^dim` neighboring cells of the grid cost more than a
+/// brute-force scan, so the pairwise metrics fall back to `O(N^2)` loops.
+const MAX_GRID_DIM: usize = 6;
+
+/// Returns the surface area of the unit n-sphere `S^n` (embedded in `R^(n+1)`).
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::sphere_area;
+/// use approx_eq::assert_approx_eq;
+///
+/// assert_approx_eq!(sphere_area(2), 4.0 * std::f64::consts::PI);
+/// ```
+pub fn sphere_area(n: usize) -> f64 {
+    // A_0 = 2, A_1 = 2 pi, A_n = 2 pi / (n - 1) * A_{n-2}
+    let mut res = if n.is_multiple_of(2) { 2.0 } else { 2.0 * PI };
+    let mut k = 2 + n % 2;
+    while k <= n {
+        res *= 2.0 * PI / (k - 1) as f64;
+        k += 2;
+    }
+    res
+}
+
+/// Returns the area of a spherical cap of angular radius `theta` on the unit `S^n`.
+///
+/// Arguments:
+///
+/// * `n`: The dimension of the sphere (`n >= 1`).
+/// * `theta`: The angular radius of the cap, in `[0, pi]`.
+pub fn cap_area(n: usize, theta: f64) -> f64 {
+    assert!(n >= 1);
+    // the integral of sin^(n-1) over [0, theta]
+    let integral = sine_power_cdf(n - 1, theta.clamp(0.0, PI)) - sine_power_cdf(n - 1, 0.0);
+    sphere_area(n - 1) * integral
+}
+
+/// Returns the normalized area (the fraction of the whole sphere) of a spherical cap of
+/// angular radius `theta` on `S^n`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::cap_fraction;
+/// use approx_eq::assert_approx_eq;
+///
+/// // Archimedes: on S^2 the cap fraction is linear in the height
+/// let theta = 1.0_f64;
+/// assert_approx_eq!(cap_fraction(2, theta), (1.0 - theta.cos()) / 2.0);
+/// ```
+pub fn cap_fraction(n: usize, theta: f64) -> f64 {
+    assert!(n >= 1);
+    let start = sine_power_cdf(n - 1, 0.0);
+    (sine_power_cdf(n - 1, theta.clamp(0.0, PI)) - start) / (sine_power_cdf(n - 1, PI) - start)
+}
+
+/// Estimates the spherical cap discrepancy of a point set on `S^n`.
+///
+/// The cap discrepancy is the supremum over all spherical caps `C` of
+/// `|#{x_i in C} / N - area(C) / area(S^n)|`. This function samples `ncaps`
+/// pseudo-random cap centers; for each center the supremum over all cap heights is
+/// computed exactly by sorting the points along the center direction. The result is
+/// therefore a lower bound of the true discrepancy which converges as `ncaps` grows.
+///
+/// Arguments:
+///
+/// * `points`: The point set, one point per row; the rows are assumed to be unit vectors.
+/// * `ncaps`: The number of cap centers to probe.
+/// * `seed`: The seed used to draw the cap centers, for reproducible estimates.
+///
+/// Returns:
+///
+/// The estimated cap discrepancy, a value in `[0, 1]`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::cap_discrepancy;
+/// use sphere_n_rs::{CylindGen, SphereN};
+///
+/// let mut sgen = SphereN::new(3, &[2, 3, 5, 7]);
+/// let pts = sgen.pop_batch(500);
+/// let disc = cap_discrepancy(pts.view(), 100, 0);
+/// assert!(disc > 0.0 && disc < 0.1);
+/// ```
+pub fn cap_discrepancy(points: ArrayView2<f64>, ncaps: usize, seed: u64) -> f64 {
+    let (npoints, dim) = points.dim();
+    if npoints == 0 {
+        return 0.0;
+    }
+    assert!(dim >= 2);
+    let n = dim - 1;
+    let total = npoints as f64;
+    let mut rng = SplitMix64::new(seed);
+    let mut heights = vec![0.0; npoints];
+    let mut res: f64 = 0.0;
+    for _ in 0..ncaps {
+        let center = rng.next_on_sphere(dim);
+        for (h, row) in heights.iter_mut().zip(points.rows()) {
+            *h = row.iter().zip(&center).map(|(x, c)| x * c).sum();
+        }
+        heights.sort_unstable_by(|a, b| b.total_cmp(a));
+        for (k, &h) in heights.iter().enumerate() {
+            // The closed cap through the (k+1)-th highest point holds k + 1 points,
+            // the open one holds k.
+            let frac = cap_fraction(n, h.clamp(-1.0, 1.0).acos());
+            let inside = (k + 1) as f64 / total;
+            let outside = k as f64 / total;
+            res = res.max((inside - frac).abs()).max((outside - frac).abs());
+        }
+    }
+    res
+}
+
+/// Measures the spread of the pairwise angles within the simplices of a triangulation.
+///
+/// For every simplex, the angle between each pair of its vertices (seen from the origin)
+/// is computed; the result is the difference between the largest and the smallest of
+/// these angles over all simplices. Well-distributed point sets yield triangulations with
+/// nearly equilateral simplices and hence a small value.
+///
+/// Arguments:
+///
+/// * `simplices`: The vertex indices of each simplex, one simplex per row.
+/// * `points`: The point set, one unit vector per row.
+pub fn discrep_2(simplices: ArrayView2<usize>, points: ArrayView2<f64>) -> f64 {
+    let mut maxq: f64 = 0.0;
+    let mut minq: f64 = 1000.0;
+    for simplex in simplices.rows() {
+        for i in 0..simplex.len() - 1 {
+            for j in i + 1..simplex.len() {
+                let dot = points.row(simplex[i]).dot(&points.row(simplex[j]));
+                let q = 1.0 - dot * dot;
+                maxq = maxq.max(q);
+                minq = minq.min(q);
+            }
+        }
+    }
+    (maxq.sqrt().asin() - minq.sqrt().asin()).abs()
+}
+
+/// Computes the dispersion of a point set on `S^2` or `S^3` (or any low-dimensional
+/// sphere) from the facets of its convex hull, see [`discrep_2`].
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::dispersion;
+/// use sphere_n_rs::{CylindGen, CylindN};
+///
+/// let mut cgen = CylindN::new(2, &[2, 3, 5]);
+/// let pts = cgen.pop_batch(200);
+/// assert!(dispersion(pts.view()) < 1.0);
+/// ```
+pub fn dispersion(points: ArrayView2<f64>) -> f64 {
+    let simplices = convex_hull(points);
+    if simplices.nrows() == 0 {
+        return 0.0;
+    }
+    discrep_2(simplices.view(), points)
+}
+
+#[inline]
+fn chord_sq(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Converts a chordal distance between unit vectors into a geodesic (angular) distance.
+#[inline]
+fn chord_to_angle(chord: f64) -> f64 {
+    2.0 * (chord / 2.0).min(1.0).asin()
+}
+
+/// Returns the typical chordal spacing of `npoints` evenly spread points on `S^n`.
+fn typical_spacing(n: usize, npoints: usize) -> f64 {
+    (sphere_area(n) / npoints as f64)
+        .powf(1.0 / n as f64)
+        .min(2.0)
+}
+
+/// Buckets points into a hashed grid of cubic cells of side `h`, so that all points
+/// within a chordal distance `h` of a query are found in its `3^dim` neighboring cells.
+struct CellGrid<'a> {
+    rows: &'a [Vec<f64>],
+    h: f64,
+    cells: HashMap<Vec<i64>, Vec<usize>>,
+}
+
+impl<'a> CellGrid<'a> {
+    fn new(rows: &'a [Vec<f64>], h: f64) -> Self {
+        let mut cells: HashMap<Vec<i64>, Vec<usize>> = HashMap::new();
+        for (i, row) in rows.iter().enumerate() {
+            cells.entry(Self::key(row, h)).or_default().push(i);
+        }
+        CellGrid { rows, h, cells }
+    }
+
+    fn key(pt: &[f64], h: f64) -> Vec<i64> {
+        pt.iter().map(|x| (x / h).floor() as i64).collect()
+    }
+
+    /// Calls `visit` with the indices of the points in the cells around `pt`.
+    fn for_each_neighbor(&self, pt: &[f64], mut visit: impl FnMut(usize)) {
+        let center = Self::key(pt, self.h);
+        let mut offset = vec![-1i64; center.len()];
+        loop {
+            let key: Vec<i64> = center.iter().zip(&offset).map(|(c, o)| c + o).collect();
+            if let Some(bucket) = self.cells.get(&key) {
+                bucket.iter().for_each(|&j| visit(j));
+            }
+            // odometer over {-1, 0, 1}^dim
+            let mut k = 0;
+            while k < offset.len() && offset[k] == 1 {
+                offset[k] = -1;
+                k += 1;
+            }
+            if k == offset.len() {
+                break;
+            }
+            offset[k] += 1;
+        }
+    }
+
+    /// Returns the smallest squared chordal distance between two points in neighboring
+    /// cells (exact whenever it does not exceed `h^2`).
+    fn closest_pair_sq(&self) -> f64 {
+        let mut best = f64::INFINITY;
+        for (i, row) in self.rows.iter().enumerate() {
+            self.for_each_neighbor(row, |j| {
+                if j > i {
+                    best = best.min(chord_sq(row, &self.rows[j]));
+                }
+            });
+        }
+        best
+    }
+
+    /// Returns the squared chordal distance from `pt` to its nearest point in the
+    /// neighboring cells (exact whenever it does not exceed `h^2`).
+    fn nearest_sq(&self, pt: &[f64]) -> f64 {
+        let mut best = f64::INFINITY;
+        self.for_each_neighbor(pt, |j| best = best.min(chord_sq(pt, &self.rows[j])));
+        best
+    }
+}
+
+fn collect_rows(points: ArrayView2<f64>) -> Vec<Vec<f64>> {
+    points.rows().into_iter().map(|r| r.to_vec()).collect()
+}
+
+fn closest_pair_sq_brute(rows: &[Vec<f64>]) -> f64 {
+    let mut best = f64::INFINITY;
+    for (i, a) in rows.iter().enumerate() {
+        for b in &rows[i + 1..] {
+            best = best.min(chord_sq(a, b));
+        }
+    }
+    best
+}
+
+fn nearest_sq_brute(rows: &[Vec<f64>], pt: &[f64]) -> f64 {
+    rows.iter()
+        .map(|r| chord_sq(r, pt))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Computes the minimum pairwise geodesic distance of a point set on `S^n`.
+///
+/// For low dimensions the points are bucketed into a grid sized after the typical point
+/// spacing, so that only nearby pairs are compared; the cell size is doubled until the
+/// closest pair is certified.
+///
+/// Arguments:
+///
+/// * `points`: The point set, one unit vector per row.
+///
+/// Returns:
+///
+/// The minimum angle (in radians) between two distinct points of the set, or `pi` if the
+/// set has fewer than two points.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::min_separation;
+/// use sphere_n_rs::{CylindGen, CylindN};
+///
+/// let mut cgen = CylindN::new(2, &[2, 3, 5]);
+/// let pts = cgen.pop_batch(1000);
+/// assert!(min_separation(pts.view()) > 0.01);
+/// ```
+pub fn min_separation(points: ArrayView2<f64>) -> f64 {
+    let rows = collect_rows(points);
+    if rows.len() < 2 {
+        return PI;
+    }
+    let dim = rows[0].len();
+    let best_sq = if dim <= MAX_GRID_DIM {
+        let mut h = typical_spacing(dim - 1, rows.len());
+        loop {
+            let best_sq = CellGrid::new(&rows, h).closest_pair_sq();
+            if best_sq <= h * h || h >= 2.0 {
+                break best_sq;
+            }
+            h = (2.0 * h).min(2.0);
+        }
+    } else {
+        closest_pair_sq_brute(&rows)
+    };
+    chord_to_angle(best_sq.sqrt())
+}
+
+/// Estimates the covering radius of a point set on `S^n`, i.e. the largest geodesic
+/// distance from any point of the sphere to its nearest point of the set.
+///
+/// The supremum is estimated by probing `nprobes` pseudo-random points of the sphere, so
+/// the result is a lower bound which converges as `nprobes` grows. Nearest neighbors are
+/// looked up in a grid for low dimensions.
+///
+/// Arguments:
+///
+/// * `points`: The point set, one unit vector per row.
+/// * `nprobes`: The number of probe points.
+/// * `seed`: The seed used to draw the probe points.
+///
+/// Returns:
+///
+/// The estimated covering radius in radians.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::covering_radius;
+/// use sphere_n_rs::{CylindGen, CylindN};
+///
+/// let mut cgen = CylindN::new(2, &[2, 3, 5]);
+/// let pts = cgen.pop_batch(1000);
+/// assert!(covering_radius(pts.view(), 10000, 0) < 0.2);
+/// ```
+pub fn covering_radius(points: ArrayView2<f64>, nprobes: usize, seed: u64) -> f64 {
+    let rows = collect_rows(points);
+    if rows.is_empty() {
+        return PI;
+    }
+    let dim = rows[0].len();
+    let mut rng = SplitMix64::new(seed);
+    let grid = if dim <= MAX_GRID_DIM {
+        let h = (2.0 * typical_spacing(dim - 1, rows.len())).min(2.0);
+        Some(CellGrid::new(&rows, h))
+    } else {
+        None
+    };
+    let mut res: f64 = 0.0;
+    for _ in 0..nprobes {
+        let probe = rng.next_on_sphere(dim);
+        let best_sq = match &grid {
+            Some(grid) => {
+                let best_sq = grid.nearest_sq(&probe);
+                if best_sq <= grid.h * grid.h {
+                    best_sq
+                } else {
+                    nearest_sq_brute(&rows, &probe)
+                }
+            }
+            None => nearest_sq_brute(&rows, &probe),
+        };
+        res = res.max(chord_to_angle(best_sq.sqrt()));
+    }
+    res
+}
+
+/// Computes the Riesz s-energy of a point set on `S^n`.
+///
+/// The energy is `sum_{i != j} |x_i - x_j|^(-s)` over chordal distances for `s > 0`, and
+/// the logarithmic energy `sum_{i != j} ln(1 / |x_i - x_j|)` for `s == 0`. Every pair
+/// contributes, so the cost is `O(N^2)`.
+///
+/// Arguments:
+///
+/// * `points`: The point set, one unit vector per row.
+/// * `s`: The Riesz exponent (`s >= 0`).
+///
+/// Returns:
+///
+/// The energy, or infinity if two points coincide.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::metrics::riesz_energy;
+/// use ndarray::array;
+/// use approx_eq::assert_approx_eq;
+///
+/// let pts = array![[0.0, 0.0, 1.0], [0.0, 0.0, -1.0]];
+/// assert_approx_eq!(riesz_energy(pts.view(), 1.0), 1.0);
+/// ```
+pub fn riesz_energy(points: ArrayView2<f64>, s: f64) -> f64 {
+    assert!(s >= 0.0);
+    let rows = collect_rows(points);
+    let mut res = 0.0;
+    for (i, a) in rows.iter().enumerate() {
+        for b in &rows[i + 1..] {
+            let d_sq = chord_sq(a, b);
+            res += if s == 0.0 {
+                -0.5 * d_sq.ln()
+            } else {
+                d_sq.powf(-0.5 * s)
+            };
+        }
+    }
+    2.0 * res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cylind_n::{CylindGen, CylindN};
+    use crate::rng::random_sphere_points;
+    use crate::sphere_n::SphereN;
+    use approx_eq::assert_approx_eq;
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_sphere_area() {
+        assert_approx_eq!(sphere_area(0), 2.0);
+        assert_approx_eq!(sphere_area(1), 2.0 * PI);
+        assert_approx_eq!(sphere_area(2), 4.0 * PI);
+        assert_approx_eq!(sphere_area(3), 2.0 * PI * PI);
+        assert_approx_eq!(sphere_area(4), 8.0 * PI * PI / 3.0);
+    }
+
+    #[test]
+    fn test_cap_area() {
+        for n in 1..8 {
+            assert_approx_eq!(cap_area(n, PI), sphere_area(n));
+            assert_approx_eq!(cap_fraction(n, PI / 2.0), 0.5);
+            assert_approx_eq!(cap_fraction(n, 0.3) + cap_fraction(n, PI - 0.3), 1.0);
+        }
+        assert_approx_eq!(cap_fraction(1, 0.3), 0.3 / PI);
+        // S^3: (2 theta - sin(2 theta)) / (2 pi)
+        assert_approx_eq!(cap_fraction(3, 0.7), (1.4 - 1.4_f64.sin()) / (2.0 * PI));
+    }
+
+    #[test]
+    fn test_cap_discrepancy_lds_vs_random() {
+        let npoints = 1000;
+        let mut sgen = SphereN::new(3, &PRIME_TABLE); // points on S^4
+        let lds = cap_discrepancy(sgen.pop_batch(npoints).view(), 200, 1);
+        let rand = cap_discrepancy(random_sphere_points(4, npoints, 7).view(), 200, 1);
+        assert!(lds < rand, "SphereN {} vs random {}", lds, rand);
+
+        let mut cgen = CylindN::new(2, &PRIME_TABLE);
+        let cyl = cap_discrepancy(cgen.pop_batch(npoints).view(), 200, 1);
+        let rand = cap_discrepancy(random_sphere_points(2, npoints, 7).view(), 200, 1);
+        assert!(cyl < rand, "CylindN {} vs random {}", cyl, rand);
+    }
+
+    #[test]
+    fn test_dispersion_sphere_n() {
+        // reference value of `run_lds` (600 points) from the original sketches; note that
+        // `SphereN::new(3, ..)` yields points in R^5
+        let mut sgen = SphereN::new(3, &[2, 3, 5, 7]);
+        let measure = dispersion(sgen.pop_batch(600).view());
+        assert_approx_eq!(measure, 0.9125914, 1e-6);
+    }
+
+    #[test]
+    fn test_dispersion_cylind_n() {
+        let mut cgen = CylindN::new(4, &[2, 3, 5, 7]);
+        let measure = dispersion(cgen.pop_batch(600).view());
+        assert_approx_eq!(measure, 1.0505837105828988, 1e-6);
+    }
+
+    #[test]
+    fn test_dispersion_s2_lds_vs_random() {
+        let mut cgen = CylindN::new(2, &PRIME_TABLE);
+        let lds = dispersion(cgen.pop_batch(400).view());
+        let rand = dispersion(random_sphere_points(2, 400, 3).view());
+        assert!(lds < rand, "CylindN {} vs random {}", lds, rand);
+    }
+
+    fn octahedron() -> ndarray::Array2<f64> {
+        ndarray::array![
+            [1.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, -1.0],
+        ]
+    }
+
+    #[test]
+    fn test_min_separation() {
+        assert_approx_eq!(min_separation(octahedron().view()), PI / 2.0);
+        // the grid search agrees with the brute-force scan
+        for (n, npoints) in [(1, 500), (2, 2000), (3, 1000), (4, 800)] {
+            let pts = random_sphere_points(n, npoints, 9);
+            let brute = chord_to_angle(closest_pair_sq_brute(&collect_rows(pts.view())).sqrt());
+            assert_approx_eq!(min_separation(pts.view()), brute);
+        }
+        let mut sgen = SphereN::new(3, &PRIME_TABLE);
+        let lds = min_separation(sgen.pop_batch(1000).view());
+        let rand = min_separation(random_sphere_points(4, 1000, 9).view());
+        assert!(lds > rand, "SphereN {} vs random {}", lds, rand);
+    }
+
+    #[test]
+    fn test_covering_radius() {
+        // the deepest holes of the octahedron are the centers of its faces
+        let radius = covering_radius(octahedron().view(), 20000, 2);
+        let exact = (1.0 / 3.0_f64.sqrt()).acos();
+        assert!(radius <= exact + 1e-12 && radius > exact - 0.05);
+
+        let mut cgen = CylindN::new(2, &PRIME_TABLE);
+        let lds = covering_radius(cgen.pop_batch(1000).view(), 5000, 2);
+        let rand = covering_radius(random_sphere_points(2, 1000, 9).view(), 5000, 2);
+        assert!(lds < rand, "CylindN {} vs random {}", lds, rand);
+    }
+
+    #[test]
+    fn test_riesz_energy() {
+        // 24 ordered pairs at chordal distance sqrt(2) and 6 at distance 2
+        let pts = octahedron();
+        assert_approx_eq!(riesz_energy(pts.view(), 1.0), 24.0 / 2.0_f64.sqrt() + 3.0);
+        assert_approx_eq!(
+            riesz_energy(pts.view(), 0.0),
+            -24.0 * 2.0_f64.sqrt().ln() - 6.0 * 2.0_f64.ln()
+        );
+
+        let mut cgen = CylindN::new(2, &PRIME_TABLE);
+        let lds = riesz_energy(cgen.pop_batch(500).view(), 1.0);
+        let rand = riesz_energy(random_sphere_points(2, 500, 9).view(), 1.0);
+        assert!(lds < rand, "CylindN {} vs random {}", lds, rand);
+    }
+}
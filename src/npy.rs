@@ -0,0 +1,471 @@
+use crate::io::invalid_data;
+use ndarray::{Array2, ArrayView2};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// The element type of an array in the NumPy format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtype {
+    /// Little-endian 32-bit float, `<f4`.
+    F32,
+    /// Little-endian 64-bit float, `<f8`.
+    F64,
+}
+
+impl Dtype {
+    fn size(&self) -> usize {
+        match self {
+            Dtype::F32 => 4,
+            Dtype::F64 => 8,
+        }
+    }
+
+    fn descr(&self) -> &'static str {
+        match self {
+            Dtype::F32 => "<f4",
+            Dtype::F64 => "<f8",
+        }
+    }
+}
+
+/// Writes a two-dimensional array in the NumPy `.npy` format (version 1.0, C order), so that
+/// it can be loaded with `numpy.load`.
+///
+/// Arguments:
+///
+/// * `points`: The array to write, e.g. a batch of points from `pop_batch`.
+/// * `dtype`: The element type of the file; values are rounded to `f32` for `Dtype::F32`.
+/// * `writer`: The destination.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::npy::{read_npy, write_npy, Dtype};
+/// use sphere_n_rs::{CylindGen, SphereN};
+///
+/// let pts = SphereN::new(3, &[2, 3, 5, 7]).pop_batch(10);
+/// let mut buf = Vec::new();
+/// write_npy(pts.view(), Dtype::F64, &mut buf).unwrap();
+/// assert_eq!(read_npy(&buf[..]).unwrap(), pts);
+/// ```
+pub fn write_npy<W: Write>(points: ArrayView2<f64>, dtype: Dtype, mut writer: W) -> io::Result<()> {
+    let (nrows, ncols) = points.dim();
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        dtype.descr(),
+        nrows,
+        ncols
+    );
+    // pad with spaces so that the data starts at a multiple of 64 bytes
+    let total = MAGIC.len() + 4 + header.len() + 1;
+    header.extend(std::iter::repeat_n(' ', (64 - total % 64) % 64));
+    header.push('\n');
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[1, 0])?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    let mut bytes = Vec::with_capacity(nrows * ncols * dtype.size());
+    for &x in points.iter() {
+        match dtype {
+            Dtype::F32 => bytes.extend((x as f32).to_le_bytes()),
+            Dtype::F64 => bytes.extend(x.to_le_bytes()),
+        }
+    }
+    writer.write_all(&bytes)
+}
+
+/// Writes named arrays to an uncompressed NumPy `.npz` archive, as `numpy.savez` does; the
+/// arrays are stored as `<name>.npy` entries and loaded back under `<name>`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::npy::{read_npz, write_npz, Dtype};
+/// use sphere_n_rs::{CylindGen, CylindN};
+///
+/// let pts = CylindN::new(2, &[2, 3]).pop_batch(10);
+/// let mut buf = Vec::new();
+/// write_npz(&[("points", pts.view())], Dtype::F32, &mut buf).unwrap();
+/// let arrays = read_npz(&buf[..]).unwrap();
+/// assert_eq!(arrays[0].0, "points");
+/// assert!((&arrays[0].1 - &pts).iter().all(|x| x.abs() < 1e-6));
+/// ```
+pub fn write_npz<W: Write>(
+    arrays: &[(&str, ArrayView2<f64>)],
+    dtype: Dtype,
+    mut writer: W,
+) -> io::Result<()> {
+    let mut offset = 0usize;
+    let mut central = Vec::new();
+    for (name, points) in arrays {
+        let mut data = Vec::new();
+        write_npy(*points, dtype, &mut data)?;
+        let name = format!("{}.npy", name);
+        let crc = crc32(&data);
+        let (size, name_len) = (zip_u32(data.len())?, name.len() as u16);
+        // local file header: version 2.0, no flags, stored, DOS date 1980-01-01
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend(0x0403_4b50_u32.to_le_bytes());
+        local.extend([20, 0, 0, 0, 0, 0, 0, 0, 0x21, 0]);
+        local.extend(crc.to_le_bytes());
+        local.extend(size.to_le_bytes());
+        local.extend(size.to_le_bytes());
+        local.extend(name_len.to_le_bytes());
+        local.extend([0, 0]);
+        local.extend(name.as_bytes());
+        // central directory entry
+        central.extend(0x0201_4b50_u32.to_le_bytes());
+        central.extend([20, 0]);
+        central.extend(&local[4..30]);
+        central.extend([0; 10]); // comment length, disk, internal and external attributes
+        central.extend(zip_u32(offset)?.to_le_bytes());
+        central.extend(name.as_bytes());
+        writer.write_all(&local)?;
+        writer.write_all(&data)?;
+        offset += local.len() + data.len();
+    }
+    // end of central directory record
+    let count = arrays.len() as u16;
+    let mut end = Vec::with_capacity(22);
+    end.extend(0x0605_4b50_u32.to_le_bytes());
+    end.extend([0; 4]);
+    end.extend(count.to_le_bytes());
+    end.extend(count.to_le_bytes());
+    end.extend(zip_u32(central.len())?.to_le_bytes());
+    end.extend(zip_u32(offset)?.to_le_bytes());
+    end.extend([0, 0]);
+    writer.write_all(&central)?;
+    writer.write_all(&end)
+}
+
+/// Reads the `<f4`/`<f8` arrays of an uncompressed NumPy `.npz` archive (as written by
+/// `numpy.savez` or `write_npz`), returning them with their names in archive order. Archives
+/// from `numpy.savez_compressed` are not supported.
+pub fn read_npz<R: Read>(mut reader: R) -> io::Result<Vec<(String, Array2<f64>)>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    // every offset comes from the archive, so all the slicing is checked
+    let slice_at = |pos: usize, len: usize| -> io::Result<&[u8]> {
+        pos.checked_add(len)
+            .and_then(|end| bytes.get(pos..end))
+            .ok_or_else(|| invalid_data("truncated .npz archive".to_string()))
+    };
+    let u16_at = |pos: usize| -> io::Result<usize> {
+        slice_at(pos, 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |pos: usize| -> io::Result<usize> {
+        slice_at(pos, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+    let u64_at = |pos: usize| -> io::Result<usize> {
+        slice_at(pos, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize)
+    };
+
+    // the end of central directory record, searched backwards past a possible comment
+    let end = (0..bytes.len().saturating_sub(21))
+        .rev()
+        .find(|&pos| bytes[pos..pos + 4] == 0x0605_4b50_u32.to_le_bytes())
+        .ok_or_else(|| invalid_data("not a .npz archive".to_string()))?;
+    let count = u16_at(end + 10)?;
+    let mut pos = u32_at(end + 16)?;
+
+    let mut res = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(pos)? != 0x0201_4b50 {
+            return Err(invalid_data("corrupt .npz central directory".to_string()));
+        }
+        let method = u16_at(pos + 10)?;
+        let crc = u32_at(pos + 16)? as u32;
+        let mut size = u32_at(pos + 20)?;
+        let mut uncompressed = u32_at(pos + 24)?;
+        let name_len = u16_at(pos + 28)?;
+        let extra_len = u16_at(pos + 30)?;
+        let comment_len = u16_at(pos + 32)?;
+        let mut offset = u32_at(pos + 42)?;
+        let name = String::from_utf8_lossy(slice_at(pos + 46, name_len)?).into_owned();
+        // the ZIP64 extra field holds the values that overflow 32 bits, in this order
+        let mut extra = pos + 46 + name_len;
+        let extra_end = extra + extra_len;
+        slice_at(extra, extra_len + comment_len)?;
+        while extra + 4 <= extra_end {
+            let (id, len) = (u16_at(extra)?, u16_at(extra + 2)?);
+            if id == 0x0001 {
+                let mut field = extra + 4;
+                for value in [&mut uncompressed, &mut size, &mut offset] {
+                    if *value == 0xFFFF_FFFF {
+                        *value = u64_at(field)?;
+                        field += 8;
+                    }
+                }
+            }
+            extra += 4 + len;
+        }
+        pos = extra_end + comment_len;
+
+        if method != 0 {
+            return Err(invalid_data(format!(
+                "{}: compressed entries are not supported",
+                name
+            )));
+        }
+        let local_header = slice_at(offset, 30)?;
+        let local_extra = u16::from_le_bytes([local_header[26], local_header[27]]) as usize
+            + u16::from_le_bytes([local_header[28], local_header[29]]) as usize;
+        let data = slice_at(offset + 30 + local_extra, size)?;
+        if crc32(data) != crc {
+            return Err(invalid_data(format!("{}: CRC mismatch", name)));
+        }
+        let key = name.strip_suffix(".npy").unwrap_or(&name).to_string();
+        res.push((key, read_npy(data)?));
+    }
+    Ok(res)
+}
+
+fn zip_u32(value: usize) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "arrays larger than 4 GiB are not supported in .npz archives",
+        )
+    })
+}
+
+/// The CRC-32 checksum (IEEE polynomial) used by the ZIP format.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Reads a one- or two-dimensional `<f4` or `<f8` array in the NumPy `.npy` format, as
+/// written by `numpy.save`; the values are converted to `f64`. A one-dimensional array is
+/// read as a single column.
+///
+/// Returns:
+///
+/// The array, or an `InvalidData` error if the data is not a supported `.npy` array.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::npy::read_npy;
+///
+/// let mut data = b"\x93NUMPY\x01\x00".to_vec();
+/// let header = "{'descr': '<f8', 'fortran_order': False, 'shape': (1, 2), }";
+/// let header = format!("{:<117}\n", header);
+/// data.extend((header.len() as u16).to_le_bytes());
+/// data.extend(header.as_bytes());
+/// data.extend(0.6_f64.to_le_bytes());
+/// data.extend(0.8_f64.to_le_bytes());
+/// let pts = read_npy(&data[..]).unwrap();
+/// assert_eq!(pts.dim(), (1, 2));
+/// assert_eq!(pts[[0, 1]], 0.8);
+/// ```
+pub fn read_npy<R: Read>(mut reader: R) -> io::Result<Array2<f64>> {
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..6] != MAGIC {
+        return Err(invalid_data("not a .npy file".to_string()));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        v => return Err(invalid_data(format!("unsupported .npy version {}", v))),
+    };
+    let mut header = Vec::new();
+    (&mut reader)
+        .take(header_len as u64)
+        .read_to_end(&mut header)?;
+    if header.len() != header_len {
+        return Err(invalid_data("truncated .npy header".to_string()));
+    }
+    let header = String::from_utf8_lossy(&header);
+    let (dtype, fortran_order, shape) = parse_header(&header)?;
+
+    let (nrows, ncols) = match shape[..] {
+        [n] => (n, 1),
+        [n, d] => (n, d),
+        _ => return Err(invalid_data(format!("unsupported shape {:?}", shape))),
+    };
+    // the shape comes from the file, so the size is checked before anything is read
+    let len = nrows
+        .checked_mul(ncols)
+        .and_then(|n| n.checked_mul(dtype.size()))
+        .ok_or_else(|| invalid_data(format!("shape {:?} is too large", shape)))?;
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(invalid_data(format!(
+            "truncated .npy data: expected {} bytes, found {}",
+            len,
+            bytes.len()
+        )));
+    }
+    let values: Vec<f64> = match dtype {
+        Dtype::F32 => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64)
+            .collect(),
+        Dtype::F64 => bytes
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect(),
+    };
+    let res = if fortran_order {
+        Array2::from_shape_vec((ncols, nrows), values).map(|a| a.reversed_axes())
+    } else {
+        Array2::from_shape_vec((nrows, ncols), values)
+    };
+    res.map_err(|e| invalid_data(e.to_string()))
+}
+
+/// Parses the Python dict literal of a `.npy` header into the dtype, the storage order
+/// and the shape.
+fn parse_header(header: &str) -> io::Result<(Dtype, bool, Vec<usize>)> {
+    let value_of = |key: &str| -> io::Result<&str> {
+        let start = header
+            .find(&format!("'{}'", key))
+            .ok_or_else(|| invalid_data(format!("missing `{}` in .npy header", key)))?;
+        let rest = &header[start + key.len() + 2..];
+        Ok(rest.trim_start().trim_start_matches(':').trim_start())
+    };
+    let descr = value_of("descr")?;
+    let dtype = if descr.starts_with("'<f8'") {
+        Dtype::F64
+    } else if descr.starts_with("'<f4'") {
+        Dtype::F32
+    } else {
+        let end = descr.find(',').unwrap_or(descr.len());
+        return Err(invalid_data(format!("unsupported dtype {}", &descr[..end])));
+    };
+    let fortran_order = value_of("fortran_order")?.starts_with("True");
+    let shape = value_of("shape")?;
+    let end = shape
+        .find(')')
+        .ok_or_else(|| invalid_data("malformed shape in .npy header".to_string()))?;
+    let shape = shape[..end]
+        .trim_start_matches('(')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|e| invalid_data(e.to_string())))
+        .collect::<io::Result<Vec<usize>>>()?;
+    Ok((dtype, fortran_order, shape))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn npy_bytes(header: &str, values: &[u8]) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend([1, 0]);
+        let header = format!("{:<117}\n", header);
+        data.extend((header.len() as u16).to_le_bytes());
+        data.extend(header.as_bytes());
+        data.extend(values);
+        data
+    }
+
+    #[test]
+    fn test_read_npy_f32_fortran() {
+        let values: Vec<u8> = [1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let data = npy_bytes(
+            "{'descr': '<f4', 'fortran_order': True, 'shape': (2, 3), }",
+            &values,
+        );
+        let pts = read_npy(&data[..]).unwrap();
+        assert_eq!(pts.dim(), (2, 3));
+        // column-major storage
+        assert_eq!(pts.row(0).to_vec(), vec![1.0, 3.0, 5.0]);
+    }
+
+    #[test]
+    fn test_write_npy_header() {
+        let pts = Array2::from_shape_vec((2, 3), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let mut buf = Vec::new();
+        write_npy(pts.view(), Dtype::F32, &mut buf).unwrap();
+        // the data is aligned to 64 bytes, after a header numpy can parse
+        assert_eq!(buf.len(), 128 + 6 * 4);
+        let header = std::str::from_utf8(&buf[10..128]).unwrap();
+        assert!(header.starts_with("{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with('\n'));
+        assert_eq!(read_npy(&buf[..]).unwrap(), pts);
+    }
+
+    #[test]
+    fn test_npz_round_trip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        let a = Array2::from_shape_fn((5, 3), |(i, j)| (i * 3 + j) as f64 / 7.0);
+        let b = Array2::from_shape_fn((2, 4), |(i, j)| (i + j) as f64);
+        let mut buf = Vec::new();
+        write_npz(&[("a", a.view()), ("b", b.view())], Dtype::F64, &mut buf).unwrap();
+        let arrays = read_npz(&buf[..]).unwrap();
+        assert_eq!(arrays, vec![("a".to_string(), a), ("b".to_string(), b)]);
+        // a name length past the end of the archive
+        let end = buf.len() - 22;
+        let central = u32::from_le_bytes(buf[end + 16..end + 20].try_into().unwrap()) as usize;
+        let mut bad = buf.clone();
+        bad[central + 28..central + 30].copy_from_slice(&0xFFFF_u16.to_le_bytes());
+        assert_eq!(
+            read_npz(&bad[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        // a local header offset past the end of the archive
+        let mut bad = buf.clone();
+        bad[central + 42..central + 46].copy_from_slice(&0xFFFF_FFF0_u32.to_le_bytes());
+        assert!(read_npz(&bad[..]).is_err());
+        // corrupt a data byte
+        buf[100] ^= 1;
+        assert!(read_npz(&buf[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_npy_errors() {
+        let data = npy_bytes(
+            "{'descr': '<i8', 'fortran_order': False, 'shape': (1,), }",
+            &[0; 8],
+        );
+        assert!(read_npy(&data[..]).is_err());
+        assert!(read_npy(&b"not numpy"[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_npy_oversized_shape() {
+        // the shape claims far more data than the file holds
+        let data = npy_bytes(
+            "{'descr': '<f8', 'fortran_order': False, 'shape': (100000000000000, 3), }",
+            &[0; 24],
+        );
+        let err = read_npy(&data[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let data = npy_bytes(
+            "{'descr': '<f8', 'fortran_order': False, 'shape': (18446744073709551615, 3), }",
+            &[],
+        );
+        assert_eq!(
+            read_npy(&data[..]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::sphere_gen;
+use lds_rs::lds::{Circle, HaltonN};
+
+/// A factor of a product manifold, for `ProductGen::from_factors`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Factor {
+    /// The uniform sphere `S^n`, consuming `n` bases.
+    Sphere(usize),
+    /// The unit cube `[0, 1]^k`, consuming `k` bases.
+    Cube(usize),
+}
+
+impl Factor {
+    /// Returns the number of bases consumed by the factor.
+    #[inline]
+    pub fn num_bases(&self) -> usize {
+        match *self {
+            Factor::Sphere(n) => n,
+            Factor::Cube(k) => k,
+        }
+    }
+
+    fn build(&self, base: &[usize]) -> Box<dyn CylindGen> {
+        match *self {
+            Factor::Sphere(n) => sphere_gen(n, base),
+            Factor::Cube(k) => Box::new(Cube {
+                halton: HaltonN::new(&base[..k]),
+                k,
+            }),
+        }
+    }
+}
+
+/// The unit cube `[0, 1]^k` of `Factor::Cube`; `HaltonN` does not report its dimension.
+struct Cube {
+    halton: HaltonN,
+    k: usize,
+}
+
+impl CylindGen for Cube {
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.halton.pop_vec()
+    }
+
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.halton.reseed(seed);
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.k
+    }
+}
+
+/// Generate low-discrepancy points on a product of manifolds, such as `S^2 x S^1` or
+/// `S^n x [0, 1]^k`.
+///
+/// `CylindN` composes a circle with extra `VdCorput` dimensions; `ProductGen` generalizes
+/// this to any list of generators, concatenating their points and reseeding them jointly.
+/// For the result to be a low-discrepancy sequence of the product, the factors must be
+/// driven by distinct bases, which `from_factors` and `torus` take care of by handing out
+/// consecutive bases.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::product::{Factor, ProductGen};
+/// use sphere_n_rs::{CylindGen, PRIME_TABLE};
+///
+/// // S^2 x S^1 in R^3 x R^2
+/// let mut pgen = ProductGen::from_factors(&[Factor::Sphere(2), Factor::Sphere(1)], &PRIME_TABLE);
+/// pgen.reseed(0);
+/// assert_eq!(pgen.pop_vec().len(), 5);
+/// ```
+pub struct ProductGen {
+    factors: Vec<Box<dyn CylindGen>>,
+}
+
+impl ProductGen {
+    /// Combines existing generators; the caller is responsible for their bases being
+    /// distinct.
+    pub fn new(factors: Vec<Box<dyn CylindGen>>) -> Self {
+        assert!(!factors.is_empty());
+        ProductGen { factors }
+    }
+
+    /// Builds the product of the given factors, assigning them consecutive bases from
+    /// `base`.
+    ///
+    /// Arguments:
+    ///
+    /// * `factors`: The factors of the product, in the order of their coordinates.
+    /// * `base`: The bases of the underlying sequences; the total of `Factor::num_bases`
+    ///   over the factors are consumed.
+    ///
+    /// Returns:
+    ///
+    /// A new `ProductGen` object is being returned from the `from_factors` function.
+    pub fn from_factors(factors: &[Factor], base: &[usize]) -> Self {
+        let mut offset = 0;
+        let mut gens = Vec::with_capacity(factors.len());
+        for factor in factors {
+            let nbases = factor.num_bases();
+            gens.push(factor.build(&base[offset..offset + nbases]));
+            offset += nbases;
+        }
+        Self::new(gens)
+    }
+
+    /// Builds the n-torus `(S^1)^n`, embedded in `R^(2n)`, from `n` circles of distinct
+    /// bases.
+    pub fn torus(n: usize, base: &[usize]) -> Self {
+        Self::new(
+            base[..n]
+                .iter()
+                .map(|&b| Box::new(Circle::new(b)) as Box<dyn CylindGen>)
+                .collect(),
+        )
+    }
+}
+
+impl CylindGen for ProductGen {
+    /// Generates the next point, the concatenation of the points of all the factors.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        let mut res = Vec::new();
+        for factor in self.factors.iter_mut() {
+            res.extend(factor.pop_vec());
+        }
+        res
+    }
+
+    /// Reseeds all the factors with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        for factor in self.factors.iter_mut() {
+            factor.reseed(seed);
+        }
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.factors.iter().map(|factor| factor.dim()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx_eq::assert_approx_eq;
+    use lds_rs::lds::{VdCorput, PRIME_TABLE};
+
+    #[test]
+    fn test_torus() {
+        let mut pgen = ProductGen::torus(3, &PRIME_TABLE);
+        let mut circle = Circle::new(5);
+        for _ in 0..20 {
+            let pt = pgen.pop_vec();
+            assert_eq!(pt.len(), 6);
+            for c in pt.chunks(2) {
+                assert_approx_eq!(c[0] * c[0] + c[1] * c[1], 1.0);
+            }
+            // the third factor uses the third base
+            let expected = circle.pop();
+            assert!((pt[4] - expected[0]).abs() < 1e-12 && (pt[5] - expected[1]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sphere_times_cube() {
+        let factors = [Factor::Sphere(3), Factor::Cube(2)];
+        let mut pgen = ProductGen::from_factors(&factors, &PRIME_TABLE);
+        assert_eq!(pgen.dim(), 6);
+        pgen.reseed(0);
+        // the cube coordinates come from the bases following those of S^3
+        let (mut vdc7, mut vdc11) = (VdCorput::new(7), VdCorput::new(11));
+        for _ in 0..20 {
+            let pt = pgen.pop_vec();
+            assert_eq!(pt.len(), 6);
+            assert_approx_eq!(pt[..4].iter().map(|x| x * x).sum::<f64>(), 1.0);
+            assert_approx_eq!(pt[4], vdc7.pop());
+            assert_approx_eq!(pt[5], vdc11.pop());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Python bindings, enabled by the `python` feature and built with maturin.
+//!
+//! The extension module `sphere_n_rs` exposes the `SphereN` and `CylindN` classes. Their
+//! constructors take the same `n` and bases as the Rust ones (the bases default to the
+//! primes), and raise `ValueError` instead of panicking on invalid arguments. Batches are
+//! returned as two-dimensional NumPy arrays, one point per row.
+//!
+//! ```python
+//! from sphere_n_rs import SphereN
+//!
+//! sgen = SphereN(5)
+//! pts = sgen.pop_batch(1000)  # numpy.ndarray of shape (1000, 7)
+//! ```
+
+use crate::builder::{CylindBuilder, SphereBuilder};
+use crate::cylind_n::{CylindGen, CylindN};
+use crate::sphere_n::SphereN;
+use lds_rs::lds::PRIME_TABLE;
+use numpy::{IntoPyArray, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Generator of points on `S^(n+1)` with the recursive sphere method.
+#[pyclass(name = "SphereN", unsendable)]
+struct PySphereN {
+    inner: SphereN,
+}
+
+#[pymethods]
+impl PySphereN {
+    #[new]
+    #[pyo3(signature = (n, base = None))]
+    fn new(n: usize, base: Option<Vec<usize>>) -> PyResult<Self> {
+        let base = base.unwrap_or_else(|| PRIME_TABLE.to_vec());
+        let inner = SphereBuilder::for_sphere_n(n)
+            .base(&base)
+            .build_sphere_n()
+            .map_err(PyValueError::new_err)?;
+        Ok(PySphereN { inner })
+    }
+
+    /// Generates the next point as a list.
+    fn pop(&mut self) -> Vec<f64> {
+        self.inner.pop_vec()
+    }
+
+    /// Generates the next `npoints` points as the rows of a NumPy array.
+    fn pop_batch<'py>(&mut self, py: Python<'py>, npoints: usize) -> Bound<'py, PyArray2<f64>> {
+        self.inner.pop_batch(npoints).into_pyarray(py)
+    }
+
+    /// Restarts the sequence at `seed`.
+    fn reseed(&mut self, seed: usize) {
+        self.inner.reseed(seed);
+    }
+
+    /// Returns the point generated after `reseed(index)`, without moving the generator.
+    fn point_at(&self, index: usize) -> PyResult<Vec<f64>> {
+        let mut state = self.inner.state();
+        state.index = index;
+        let mut sgen = SphereN::from_state(&state).map_err(PyValueError::new_err)?;
+        Ok(sgen.pop_vec())
+    }
+
+    #[getter]
+    fn n(&self) -> usize {
+        self.inner.get_n()
+    }
+
+    #[getter]
+    fn base(&self) -> Vec<usize> {
+        self.inner.get_base().to_vec()
+    }
+
+    #[getter]
+    fn index(&self) -> usize {
+        self.inner.get_index()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SphereN(n={}, base={:?}, index={})",
+            self.inner.get_n(),
+            self.inner.get_base(),
+            self.inner.get_index()
+        )
+    }
+}
+
+/// Generator of points on `S^n` with the cylindrical coordinate method.
+#[pyclass(name = "CylindN", unsendable)]
+struct PyCylindN {
+    inner: CylindN,
+}
+
+#[pymethods]
+impl PyCylindN {
+    #[new]
+    #[pyo3(signature = (n, base = None))]
+    fn new(n: usize, base: Option<Vec<usize>>) -> PyResult<Self> {
+        let base = base.unwrap_or_else(|| PRIME_TABLE.to_vec());
+        let inner = CylindBuilder::new(n)
+            .base(&base)
+            .build_cylind_n()
+            .map_err(PyValueError::new_err)?;
+        Ok(PyCylindN { inner })
+    }
+
+    /// Generates the next point as a list.
+    fn pop(&mut self) -> Vec<f64> {
+        self.inner.pop_vec()
+    }
+
+    /// Generates the next `npoints` points as the rows of a NumPy array.
+    fn pop_batch<'py>(&mut self, py: Python<'py>, npoints: usize) -> Bound<'py, PyArray2<f64>> {
+        self.inner.pop_batch(npoints).into_pyarray(py)
+    }
+
+    /// Restarts the sequence at `seed`.
+    fn reseed(&mut self, seed: usize) {
+        self.inner.reseed(seed);
+    }
+
+    /// Returns the point generated after `reseed(index)`, without moving the generator.
+    fn point_at(&self, index: usize) -> PyResult<Vec<f64>> {
+        let mut state = self.inner.state();
+        state.index = index;
+        let mut cgen = CylindN::from_state(&state).map_err(PyValueError::new_err)?;
+        Ok(cgen.pop_vec())
+    }
+
+    #[getter]
+    fn n(&self) -> usize {
+        self.inner.get_n()
+    }
+
+    #[getter]
+    fn base(&self) -> Vec<usize> {
+        self.inner.get_base().to_vec()
+    }
+
+    #[getter]
+    fn index(&self) -> usize {
+        self.inner.get_index()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CylindN(n={}, base={:?}, index={})",
+            self.inner.get_n(),
+            self.inner.get_base(),
+            self.inner.get_index()
+        )
+    }
+}
+
+/// Low-discrepancy sequences on the n-sphere.
+#[pymodule]
+fn sphere_n_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySphereN>()?;
+    m.add_class::<PyCylindN>()?;
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use crate::cylind_n::CylindGen;
+use lds_rs::lds::{Circle, VdCorput};
+use std::f64::consts::PI;
+
+/// A direction sample together with the value of its probability density with respect to
+/// the solid angle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionSample {
+    pub direction: [f64; 3],
+    pub pdf: f64,
+}
+
+/// An orthonormal frame `(tangent, bitangent, normal)` of `R^3`, used to orient samples
+/// drawn around the `z` axis towards an arbitrary normal.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::render::Frame;
+/// use approx_eq::assert_approx_eq;
+///
+/// let frame = Frame::from_normal(&[0.0, 1.0, 0.0]);
+/// let res = frame.to_world(&[0.0, 0.0, 1.0]);
+/// assert_approx_eq!(res[1], 1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub tangent: [f64; 3],
+    pub bitangent: [f64; 3],
+    pub normal: [f64; 3],
+}
+
+impl Frame {
+    /// Builds a frame around the unit vector `normal`, with the branchless construction of
+    /// Duff et al. (2017), which is continuous except across the plane `normal[2] = 0`.
+    pub fn from_normal(normal: &[f64; 3]) -> Self {
+        let [x, y, z] = *normal;
+        let sign = 1.0_f64.copysign(z);
+        let a = -1.0 / (sign + z);
+        let b = x * y * a;
+        Frame {
+            tangent: [1.0 + sign * x * x * a, sign * b, -sign * x],
+            bitangent: [b, sign + y * y * a, -y],
+            normal: *normal,
+        }
+    }
+
+    /// Maps local coordinates (with the normal along `z`) to world coordinates.
+    #[inline]
+    pub fn to_world(&self, local: &[f64; 3]) -> [f64; 3] {
+        let mut res = [0.0; 3];
+        for (i, r) in res.iter_mut().enumerate() {
+            *r = local[0] * self.tangent[i]
+                + local[1] * self.bitangent[i]
+                + local[2] * self.normal[i];
+        }
+        res
+    }
+
+    /// Maps world coordinates to local coordinates (with the normal along `z`).
+    #[inline]
+    pub fn to_local(&self, world: &[f64; 3]) -> [f64; 3] {
+        let dot = |v: &[f64; 3]| v.iter().zip(world).map(|(a, b)| a * b).sum::<f64>();
+        [dot(&self.tangent), dot(&self.bitangent), dot(&self.normal)]
+    }
+}
+
+impl Default for Frame {
+    /// The canonical frame, with the normal along `z`.
+    fn default() -> Self {
+        Frame {
+            tangent: [1.0, 0.0, 0.0],
+            bitangent: [0.0, 1.0, 0.0],
+            normal: [0.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Generate low-discrepancy directions on the hemisphere around a normal, with the
+/// cosine-weighted density `cos(theta) / pi`.
+///
+/// As in the cylindrical method of `CylindN`, a `Circle` gives the azimuth and a `VdCorput`
+/// sequence the axial coordinate, here `cos(theta) = sqrt(u)` instead of `2u - 1` (Malley's
+/// method: the projection onto the tangent plane is uniform on the disk).
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::render::CosineHemisphere;
+///
+/// let mut hgen = CosineHemisphere::new(&[0.0, 0.0, 1.0], &[2, 3]);
+/// hgen.reseed(0);
+/// let sample = hgen.pop();
+/// assert!(sample.direction[2] >= 0.0);
+/// assert!((sample.pdf - sample.direction[2] / std::f64::consts::PI).abs() < 1e-12);
+/// ```
+pub struct CosineHemisphere {
+    vdc: VdCorput,
+    circle: Circle,
+    frame: Frame,
+}
+
+impl CosineHemisphere {
+    /// The function `new` constructs a new `CosineHemisphere` object with specified
+    /// parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `normal`: The unit normal around which the directions are drawn.
+    /// * `base`: The bases of the underlying sequences; two of them are consumed, the first
+    ///   for the axial component and the second for the azimuth.
+    ///
+    /// Returns:
+    ///
+    /// A new `CosineHemisphere` object is being returned from the `new` function.
+    pub fn new(normal: &[f64; 3], base: &[usize]) -> Self {
+        CosineHemisphere {
+            vdc: VdCorput::new(base[0]),
+            circle: Circle::new(base[1]),
+            frame: Frame::from_normal(normal),
+        }
+    }
+
+    /// Generates the next direction with its density.
+    pub fn pop(&mut self) -> DirectionSample {
+        let cos_t = self.vdc.pop().sqrt();
+        let local = lift(cos_t, self.circle.pop());
+        DirectionSample {
+            direction: self.frame.to_world(&local),
+            pdf: cos_t / PI,
+        }
+    }
+
+    /// Returns the density of the given unit direction.
+    #[inline]
+    pub fn pdf(&self, direction: &[f64; 3]) -> f64 {
+        self.frame.to_local(direction)[2].max(0.0) / PI
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    pub fn reseed(&mut self, seed: usize) {
+        self.vdc.reseed(seed);
+        self.circle.reseed(seed);
+    }
+}
+
+/// Generate low-discrepancy directions uniformly distributed in the cone of half-angle
+/// `acos(cos_max)` around an axis, with the constant density `1 / (2 pi (1 - cos_max))`.
+///
+/// The axial coordinate is `cos(theta) = 1 - u (1 - cos_max)`, the cylindrical method of
+/// `CylindN` restricted to the cap; `cos_max = 0` gives the uniform hemisphere and
+/// `cos_max = -1` the whole sphere.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::render::UniformCone;
+///
+/// let mut cgen = UniformCone::new(&[1.0, 0.0, 0.0], 0.9, &[2, 3]);
+/// cgen.reseed(0);
+/// let sample = cgen.pop();
+/// assert!(sample.direction[0] >= 0.9 - 1e-12);
+/// ```
+pub struct UniformCone {
+    vdc: VdCorput,
+    circle: Circle,
+    frame: Frame,
+    cos_max: f64,
+}
+
+impl UniformCone {
+    /// The function `new` constructs a new `UniformCone` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `axis`: The unit axis of the cone.
+    /// * `cos_max`: The cosine of the half-angle of the cone, in `[-1, 1)`.
+    /// * `base`: The bases of the underlying sequences; two of them are consumed, the first
+    ///   for the axial component and the second for the azimuth.
+    ///
+    /// Returns:
+    ///
+    /// A new `UniformCone` object is being returned from the `new` function.
+    pub fn new(axis: &[f64; 3], cos_max: f64, base: &[usize]) -> Self {
+        assert!((-1.0..1.0).contains(&cos_max));
+        UniformCone {
+            vdc: VdCorput::new(base[0]),
+            circle: Circle::new(base[1]),
+            frame: Frame::from_normal(axis),
+            cos_max,
+        }
+    }
+
+    /// Generates the next direction with its density.
+    pub fn pop(&mut self) -> DirectionSample {
+        let cos_t = 1.0 - self.vdc.pop() * (1.0 - self.cos_max);
+        let local = lift(cos_t, self.circle.pop());
+        DirectionSample {
+            direction: self.frame.to_world(&local),
+            pdf: self.pdf_inside(),
+        }
+    }
+
+    /// Returns the density of the given unit direction.
+    #[inline]
+    pub fn pdf(&self, direction: &[f64; 3]) -> f64 {
+        if self.frame.to_local(direction)[2] >= self.cos_max {
+            self.pdf_inside()
+        } else {
+            0.0
+        }
+    }
+
+    #[inline]
+    fn pdf_inside(&self) -> f64 {
+        1.0 / (2.0 * PI * (1.0 - self.cos_max))
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    pub fn reseed(&mut self, seed: usize) {
+        self.vdc.reseed(seed);
+        self.circle.reseed(seed);
+    }
+}
+
+/// Lifts a point of the unit circle to the sphere at the axial coordinate `cos_t`.
+#[inline]
+fn lift(cos_t: f64, circle: [f64; 2]) -> [f64; 3] {
+    let sin_t = (1.0 - cos_t * cos_t).max(0.0).sqrt();
+    [sin_t * circle[0], sin_t * circle[1], cos_t]
+}
+
+impl CylindGen for CosineHemisphere {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.pop().direction.to_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        self.reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        3
+    }
+}
+
+impl CylindGen for UniformCone {
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.pop().direction.to_vec()
+    }
+
+    fn reseed(&mut self, seed: usize) {
+        self.reseed(seed);
+    }
+
+    fn dim(&self) -> usize {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx_eq::assert_approx_eq;
+
+    fn unit(v: [f64; 3]) -> [f64; 3] {
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        [v[0] / norm, v[1] / norm, v[2] / norm]
+    }
+
+    #[test]
+    fn test_frame_orthonormal() {
+        for normal in [[0.0, 0.0, 1.0], [0.0, 0.0, -1.0], unit([1.0, -2.0, 0.5])] {
+            let frame = Frame::from_normal(&normal);
+            let vecs = [frame.tangent, frame.bitangent, frame.normal];
+            for (i, u) in vecs.iter().enumerate() {
+                for (j, v) in vecs.iter().enumerate() {
+                    let dot: f64 = u.iter().zip(v).map(|(a, b)| a * b).sum();
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert!((dot - expected).abs() < 1e-12);
+                }
+            }
+            let local = frame.to_local(&frame.to_world(&[0.3, -0.4, 0.2]));
+            assert!((local[1] + 0.4).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_cosine_hemisphere_moments() {
+        let normal = unit([1.0, 1.0, 1.0]);
+        let mut hgen = CosineHemisphere::new(&normal, &[2, 3]);
+        let npoints = 4000;
+        let (mut mean_cos, mut mean_cos2) = (0.0, 0.0);
+        for _ in 0..npoints {
+            let sample = hgen.pop();
+            let cos_t: f64 = sample
+                .direction
+                .iter()
+                .zip(&normal)
+                .map(|(a, b)| a * b)
+                .sum();
+            assert!(cos_t >= -1e-12);
+            assert_approx_eq!(hgen.pdf(&sample.direction), sample.pdf);
+            mean_cos += cos_t / npoints as f64;
+            mean_cos2 += cos_t * cos_t / npoints as f64;
+        }
+        // E[cos] = 2 / 3 and E[cos^2] = 1 / 2 for the density cos / pi
+        assert!((mean_cos - 2.0 / 3.0).abs() < 1e-3);
+        assert!((mean_cos2 - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_uniform_cone() {
+        let axis = unit([0.0, -1.0, 1.0]);
+        let cos_max = 0.5;
+        let mut cgen = UniformCone::new(&axis, cos_max, &[2, 3]);
+        let npoints = 2000;
+        let mut mean_cos = 0.0;
+        for _ in 0..npoints {
+            let sample = cgen.pop();
+            let cos_t: f64 = sample.direction.iter().zip(&axis).map(|(a, b)| a * b).sum();
+            assert!(cos_t >= cos_max - 1e-12);
+            assert_approx_eq!(sample.pdf, 1.0 / PI);
+            mean_cos += cos_t / npoints as f64;
+        }
+        assert!((mean_cos - (1.0 + cos_max) / 2.0).abs() < 1e-3);
+        assert_eq!(cgen.pdf(&[-axis[0], -axis[1], -axis[2]]), 0.0);
+    }
+}
@@ -0,0 +1,133 @@
+use ndarray::{Array1, Array2};
+
+/// A small, deterministic pseudo-random number generator (SplitMix64).
+///
+/// It is used wherever the crate needs reproducible randomness: probing caps in the
+/// metrics, pseudo-random baselines and randomized replicates. It is not meant to be
+/// cryptographically secure.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::rng::SplitMix64;
+///
+/// let mut rng = SplitMix64::new(42);
+/// let u = rng.next_f64();
+/// assert!((0.0..1.0).contains(&u));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// Creates a new generator from the given seed.
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    /// Returns the next 64 random bits.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed value in `[0, 1)`.
+    #[inline]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns a standard normal deviate (Box-Muller transform).
+    #[inline]
+    pub fn next_gaussian(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64(); // (0, 1]
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+
+    /// Returns a uniformly distributed point on the unit sphere in `R^dim`, obtained by
+    /// normalizing a vector of independent Gaussian samples.
+    pub fn next_on_sphere(&mut self, dim: usize) -> Vec<f64> {
+        loop {
+            let mut res: Vec<f64> = (0..dim).map(|_| self.next_gaussian()).collect();
+            let norm = res.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm > 1e-12 {
+                for xi in res.iter_mut() {
+                    *xi /= norm;
+                }
+                return res;
+            }
+        }
+    }
+}
+
+/// Draws a uniformly distributed (Haar) random rotation of `R^dim`.
+///
+/// The rows of the returned `dim x dim` orthogonal matrix are obtained by Gram-Schmidt
+/// orthonormalization of independent Gaussian vectors. Rotating a low-discrepancy point
+/// set by such a matrix randomizes it while preserving its uniformity.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::rng::{random_rotation, SplitMix64};
+/// use approx_eq::assert_approx_eq;
+///
+/// let rot = random_rotation(&mut SplitMix64::new(3), 4);
+/// let prod = rot.dot(&rot.t());
+/// assert_approx_eq!(prod[[1, 1]], 1.0);
+/// assert!(prod[[1, 2]].abs() < 1e-12);
+/// ```
+pub fn random_rotation(rng: &mut SplitMix64, dim: usize) -> Array2<f64> {
+    let mut res = Array2::zeros((dim, dim));
+    let mut i = 0;
+    while i < dim {
+        let mut v = Array1::from_shape_fn(dim, |_| rng.next_gaussian());
+        for row in res.rows().into_iter().take(i) {
+            let proj = row.dot(&v);
+            v.scaled_add(-proj, &row);
+        }
+        let norm = v.dot(&v).sqrt();
+        if norm > 1e-9 {
+            res.row_mut(i).assign(&(v / norm));
+            i += 1;
+        }
+    }
+    res
+}
+
+/// Generates `npoints` pseudo-random points uniformly distributed on the n-sphere.
+///
+/// Arguments:
+///
+/// * `n`: The dimension of the sphere; the points live in `R^(n+1)`.
+/// * `npoints`: The number of points to generate.
+/// * `seed`: The seed of the underlying [`SplitMix64`] generator.
+///
+/// Returns:
+///
+/// An array of shape `(npoints, n + 1)`, one point per row.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::rng::random_sphere_points;
+///
+/// let pts = random_sphere_points(2, 100, 1);
+/// assert_eq!(pts.dim(), (100, 3));
+/// ```
+pub fn random_sphere_points(n: usize, npoints: usize, seed: u64) -> Array2<f64> {
+    let mut rng = SplitMix64::new(seed);
+    let mut res = Array2::zeros((npoints, n + 1));
+    for mut row in res.rows_mut() {
+        for (dst, src) in row.iter_mut().zip(rng.next_on_sphere(n + 1)) {
+            *dst = src;
+        }
+    }
+    res
+}
@@ -0,0 +1,173 @@
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::sphere_gen;
+use crate::zonal::{tabulate_cdf, TABLE_SIZE};
+use interp::{interp, InterpMode};
+use lds_rs::lds::VdCorput;
+
+/// The inverse CDF of the radius.
+enum Radial {
+    /// Uniform in volume, inverted in closed form.
+    Uniform { r0: f64, r1: f64, dim: i32 },
+    /// A tabulated CDF, inverted by interpolation.
+    Table { cdf: Vec<f64>, radius: Vec<f64> },
+}
+
+/// Generate low-discrepancy points in the spherical shell `r0 <= |x| <= r1` of `R^(n+1)`.
+///
+/// A uniform generator of `S^n` gives the direction and a `VdCorput` sequence the radius,
+/// mapped through the inverse CDF of the radial distribution. For points uniform in volume
+/// the radial CDF has the closed-form inverse `r = (r0^d + u (r1^d - r0^d))^(1/d)` with
+/// `d = n + 1`; for other densities the CDF is tabulated once and inverted by interpolation,
+/// as for `ZonalGen`. With `n = 1` this samples an annulus.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::shell::Shell;
+/// use sphere_n_rs::CylindGen;
+///
+/// let mut sgen = Shell::new(2, 1.0, 2.0, &[2, 3, 5]);
+/// sgen.reseed(0);
+/// let res = sgen.pop_vec();
+/// let r = res.iter().map(|x| x * x).sum::<f64>().sqrt();
+/// assert!((1.0..=2.0).contains(&r));
+/// ```
+pub struct Shell {
+    vdc: VdCorput,
+    s_gen: Box<dyn CylindGen>,
+    radial: Radial,
+}
+
+impl Shell {
+    /// The function `new` constructs a new `Shell` object sampling uniformly in volume.
+    ///
+    /// Arguments:
+    ///
+    /// * `n`: The dimension of the sphere of directions; the points live in `R^(n+1)`.
+    /// * `r0`: The inner radius, `r0 >= 0`.
+    /// * `r1`: The outer radius, `r1 > r0`.
+    /// * `base`: The bases of the underlying sequences; `n + 1` of them are consumed, the
+    ///   first for the radius and the rest for the direction.
+    ///
+    /// Returns:
+    ///
+    /// A new `Shell` object is being returned from the `new` function.
+    pub fn new(n: usize, r0: f64, r1: f64, base: &[usize]) -> Self {
+        assert!(r0 >= 0.0 && r1 > r0);
+        Shell {
+            vdc: VdCorput::new(base[0]),
+            s_gen: sphere_gen(n, &base[1..]),
+            radial: Radial::Uniform {
+                r0,
+                r1,
+                dim: (n + 1) as i32,
+            },
+        }
+    }
+
+    /// Constructs a `Shell` object whose points have the density `density(r)` with respect
+    /// to the volume, i.e. the radius has a density proportional to `density(r) r^n`. The
+    /// density must be non-negative and need not be normalized; the other arguments are as
+    /// for `new`.
+    pub fn with_density<F: Fn(f64) -> f64>(
+        n: usize,
+        r0: f64,
+        r1: f64,
+        density: F,
+        base: &[usize],
+    ) -> Self {
+        assert!(r0 >= 0.0 && r1 > r0);
+        let step = (r1 - r0) / (TABLE_SIZE - 1) as f64;
+        let radius: Vec<f64> = (0..TABLE_SIZE).map(|i| r0 + step * i as f64).collect();
+        let weights: Vec<f64> = radius
+            .iter()
+            .map(|&r| density(r) * r.powi(n as i32))
+            .collect();
+        Shell {
+            vdc: VdCorput::new(base[0]),
+            s_gen: sphere_gen(n, &base[1..]),
+            radial: Radial::Table {
+                cdf: tabulate_cdf(&weights, step),
+                radius,
+            },
+        }
+    }
+}
+
+impl CylindGen for Shell {
+    /// Generates the next point in the shell.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        let u = self.vdc.pop();
+        let r = match &self.radial {
+            Radial::Uniform { r0, r1, dim } => {
+                let (a, b) = (r0.powi(*dim), r1.powi(*dim));
+                (a + u * (b - a)).powf(1.0 / *dim as f64)
+            }
+            Radial::Table { cdf, radius } => interp(cdf, radius, u, &InterpMode::default()),
+        };
+        let mut res = self.s_gen.pop_vec();
+        for xi in res.iter_mut() {
+            *xi *= r;
+        }
+        res
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.vdc.reseed(seed);
+        self.s_gen.reseed(seed);
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.s_gen.dim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lds_rs::lds::PRIME_TABLE;
+
+    fn norm(pt: &[f64]) -> f64 {
+        pt.iter().map(|x| x * x).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn test_shell_uniform_volume() {
+        // P(|x| <= s) = (s^d - r0^d) / (r1^d - r0^d)
+        for n in [1, 2, 4] {
+            let (r0, r1, d) = (0.5_f64, 2.0_f64, (n + 1) as i32);
+            let mut sgen = Shell::new(n, r0, r1, &PRIME_TABLE);
+            let npoints = 2000;
+            let radii: Vec<f64> = (0..npoints).map(|_| norm(&sgen.pop_vec())).collect();
+            for s in [0.8, 1.2, 1.7] {
+                let count = radii.iter().filter(|&&r| r <= s).count() as f64;
+                let expected = (s.powi(d) - r0.powi(d)) / (r1.powi(d) - r0.powi(d));
+                assert!((count / npoints as f64 - expected).abs() < 2e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_shell_constant_density_matches_uniform() {
+        let mut a = Shell::new(3, 1.0, 3.0, &PRIME_TABLE);
+        let mut b = Shell::with_density(3, 1.0, 3.0, |_| 1.0, &PRIME_TABLE);
+        for _ in 0..500 {
+            let (pa, pb) = (a.pop_vec(), b.pop_vec());
+            assert!((norm(&pa) - norm(&pb)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_shell_radial_density() {
+        // in the plane, the density 1 / r on the annulus makes the radius uniform
+        let mut sgen = Shell::with_density(1, 1.0, 2.0, |r| 1.0 / r, &PRIME_TABLE);
+        let mut vdc = VdCorput::new(2);
+        for _ in 0..200 {
+            assert!((norm(&sgen.pop_vec()) - (1.0 + vdc.pop())).abs() < 1e-6);
+        }
+    }
+}
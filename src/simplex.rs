@@ -0,0 +1,155 @@
+use crate::cylind_n::CylindGen;
+use lds_rs::lds::VdCorput;
+
+/// Generate low-discrepancy points uniformly distributed on the probability simplex
+/// `{x in R^(k+1) : x_i >= 0, sum x_i = 1}`, i.e. samples of Dirichlet(1, ..., 1).
+///
+/// The points are built by stick-breaking: the `i`-th `VdCorput` stream is mapped through the
+/// inverse CDF of Beta(1, k - i), `b = 1 - (1 - u)^(1 / (k - i))`, and the fraction `b` of
+/// the remaining length is assigned to coordinate `i`. The construction is exact, so the
+/// low discrepancy of the streams carries over to the simplex.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::simplex::Simplex;
+/// use sphere_n_rs::CylindGen;
+/// use approx_eq::assert_approx_eq;
+///
+/// let mut sgen = Simplex::new(3, &[2, 3, 5]);
+/// sgen.reseed(0);
+/// let res = sgen.pop_vec();
+/// assert_eq!(res.len(), 4);
+/// assert_approx_eq!(res.iter().sum::<f64>(), 1.0);
+/// ```
+pub struct Simplex {
+    vdcs: Vec<VdCorput>,
+}
+
+impl Simplex {
+    /// The function `new` constructs a new `Simplex` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `k`: The dimension of the simplex; the points have `k + 1` coordinates.
+    /// * `base`: The bases of the underlying sequences; `k` of them are consumed.
+    ///
+    /// Returns:
+    ///
+    /// A new `Simplex` object is being returned from the `new` function.
+    pub fn new(k: usize, base: &[usize]) -> Self {
+        assert!(k >= 1);
+        Simplex {
+            vdcs: base[..k].iter().map(|&b| VdCorput::new(b)).collect(),
+        }
+    }
+}
+
+impl CylindGen for Simplex {
+    /// Generates the next point on the simplex.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        let k = self.vdcs.len();
+        let mut res = Vec::with_capacity(k + 1);
+        let mut remaining = 1.0;
+        for (i, vdc) in self.vdcs.iter_mut().enumerate() {
+            let b = 1.0 - (1.0 - vdc.pop()).powf(1.0 / (k - i) as f64);
+            res.push(remaining * b);
+            remaining *= 1.0 - b;
+        }
+        res.push(remaining);
+        res
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        for vdc in self.vdcs.iter_mut() {
+            vdc.reseed(seed);
+        }
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.vdcs.len() + 1
+    }
+}
+
+/// Maps a point of the odd-dimensional sphere `S^(2k+1)` to the simplex of dimension `k` by
+/// summing its squared coordinates in pairs.
+///
+/// Viewing `R^(2k+2)` as `C^(k+1)`, the squared moduli `|z_i|^2` of a uniform point on the
+/// sphere are uniform on the simplex, so this turns a uniform generator of `S^(2k+1)`, such
+/// as `SphereN` or `sphere_gen`, into a simplex sampler. `CylindN` is not uniform on these
+/// spheres and gives a biased sampler.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::simplex::simplex_from_sphere;
+/// use approx_eq::assert_approx_eq;
+///
+/// let res = simplex_from_sphere(&[0.6, 0.0, 0.0, 0.8]);
+/// assert_approx_eq!(res[0], 0.36);
+/// assert_approx_eq!(res[1], 0.64);
+/// ```
+pub fn simplex_from_sphere(point: &[f64]) -> Vec<f64> {
+    assert!(point.len().is_multiple_of(2));
+    point.chunks(2).map(|c| c[0] * c[0] + c[1] * c[1]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere_n::sphere_gen;
+    use lds_rs::lds::PRIME_TABLE;
+
+    /// Checks that every coordinate follows the Beta(1, k) marginal of the uniform
+    /// k-simplex, `P(x_i <= s) = 1 - (1 - s)^k`, with mean `1 / (k + 1)`.
+    fn check_marginals(pts: &[Vec<f64>], k: usize, tol: f64) {
+        let npoints = pts.len() as f64;
+        for p in pts.iter() {
+            assert!((p.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            assert!(p.iter().all(|&x| x >= 0.0));
+        }
+        for i in 0..=k {
+            let mean = pts.iter().map(|p| p[i]).sum::<f64>() / npoints;
+            assert!(
+                (mean - 1.0 / (k + 1) as f64).abs() < tol,
+                "i = {}, mean = {}",
+                i,
+                mean
+            );
+            for s in [0.05, 0.2, 0.4, 0.7] {
+                let count = pts.iter().filter(|p| p[i] <= s).count() as f64;
+                let expected = 1.0 - (1.0 - s).powi(k as i32);
+                assert!(
+                    (count / npoints - expected).abs() < tol,
+                    "i = {}, s = {}",
+                    i,
+                    s
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplex_marginals() {
+        for k in [1, 2, 4] {
+            let mut sgen = Simplex::new(k, &PRIME_TABLE);
+            let pts: Vec<Vec<f64>> = (0..3000).map(|_| sgen.pop_vec()).collect();
+            check_marginals(&pts, k, 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_simplex_from_sphere_marginals() {
+        for k in [1, 2, 3] {
+            let mut sgen = sphere_gen(2 * k + 1, &PRIME_TABLE);
+            let pts: Vec<Vec<f64>> = (0..3000)
+                .map(|_| simplex_from_sphere(&sgen.pop_vec()))
+                .collect();
+            check_marginals(&pts, k, 1e-2);
+        }
+    }
+}
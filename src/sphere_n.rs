@@ -1,12 +1,16 @@
+use crate::cylind_n::CylindGen;
 use interp::{interp, InterpMode};
 use lazy_static::lazy_static;
-use lds_rs::lds::{Sphere, VdCorput};
+use lds_rs::lds::{Circle, Sphere, VdCorput};
 use ndarray::Array1;
 use std::f64::consts::FRAC_PI_2;
 use std::f64::consts::PI; // Half of PI
 
+/// The default number of nodes of the interpolation tables.
+pub const GRID_SIZE: usize = 300;
+
 lazy_static! {
-    static ref X: Array1<f64> = Array1::linspace(0.0, PI, 300);
+    static ref X: Array1<f64> = Array1::linspace(0.0, PI, GRID_SIZE);
 }
 
 /// The struct `Gl` in Rust contains three arrays of type `f64` representing `x`, `neg_cosine`, and
@@ -25,7 +29,6 @@ struct Gl {
     x: Array1<f64>,
     neg_cosine: Array1<f64>,
     sine: Array1<f64>,
-    f2: Array1<f64>,
 }
 
 lazy_static! {
@@ -33,7 +36,6 @@ lazy_static! {
         x: X.clone(),
         neg_cosine: -X.mapv(f64::cos),
         sine: X.mapv(f64::sin),
-        f2: X.mapv(|x| (x - x.cos() * x.sin()) / 2.0),
     };
 }
 
@@ -57,7 +59,9 @@ pub trait SphereGen {
 pub struct Sphere3 {
     vdc: VdCorput,
     sphere2: Sphere,
+    x: Array1<f64>,
     tp: Array1<f64>,
+    inversion: Inversion,
 }
 
 impl Sphere3 {
@@ -73,11 +77,27 @@ impl Sphere3 {
     ///
     /// A new `Sphere3` object is being returned from the `new` function.
     pub fn new(base: &[usize]) -> Self {
+        Self::with_options(base, GRID_SIZE, Inversion::default())
+    }
+
+    /// Like `new`, but with `grid_size` nodes in the interpolation table and the given
+    /// inversion of the polar-angle CDF, as set for the `SphereN` stages above it.
+    pub(crate) fn with_options(base: &[usize], grid_size: usize, inversion: Inversion) -> Self {
+        assert!(grid_size >= 2);
+        let (x, tp) = if grid_size == GRID_SIZE {
+            // tp: 0.5 * (X.mapv(|x| x) - SINE.mapv(|x| x) + NEG_COSINE.mapv(|x| x)),
+            (X.clone(), 0.5 * (&GL.x + &GL.sine * &GL.neg_cosine))
+        } else {
+            let x = Array1::linspace(0.0, PI, grid_size);
+            let tp = x.mapv(|t| sine_power_cdf(2, t));
+            (x, tp)
+        };
         Sphere3 {
             vdc: VdCorput::new(base[0]),
             sphere2: Sphere::new(&base[1..3]),
-            // tp: 0.5 * (X.mapv(|x| x) - SINE.mapv(|x| x) + NEG_COSINE.mapv(|x| x)),
-            tp: 0.5 * (&GL.x + &GL.sine * &GL.neg_cosine),
+            x,
+            tp,
+            inversion,
         }
     }
 
@@ -92,7 +112,13 @@ impl Sphere3 {
     #[inline]
     pub fn pop(&mut self) -> [f64; 4] {
         let ti = FRAC_PI_2 * self.vdc.pop(); // map to [0, pi];
-        let xi = interp(&GL.f2.to_vec(), &X.to_vec(), ti, &InterpMode::default());
+        let xi = interp(
+            &self.tp.to_vec(),
+            &self.x.to_vec(),
+            ti,
+            &InterpMode::default(),
+        );
+        let xi = self.inversion.refine(2, xi, ti);
         let cosxi = xi.cos();
         let sinxi = xi.sin();
         let [s0, s1, s2] = self.sphere2.pop();
@@ -131,6 +157,68 @@ impl SphereGen for Sphere3 {
     }
 }
 
+/// How `SphereN` and `Sphere3` invert the CDF of their polar angle.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Inversion {
+    /// Linear interpolation in the tabulated CDF.
+    #[default]
+    Interpolate,
+    /// Linear interpolation refined by Newton iterations on the exact CDF, until the angle
+    /// changes by less than `tolerance`.
+    Newton { tolerance: f64 },
+}
+
+impl Inversion {
+    /// Checks that a Newton tolerance is positive.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match *self {
+            Inversion::Newton { tolerance } if tolerance.is_nan() || tolerance <= 0.0 => {
+                Err("the Newton tolerance must be positive".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Refines the angle `xi` interpolated for the value `ti` of the CDF of the polar
+    /// density `sin^n`.
+    fn refine(&self, n: usize, mut xi: f64, ti: f64) -> f64 {
+        if let Inversion::Newton { tolerance } = *self {
+            // the polar density is sin^n, the derivative of the CDF
+            for _ in 0..20 {
+                let slope = xi.sin().powi(n as i32);
+                if slope < 1e-300 {
+                    break;
+                }
+                let step = (sine_power_cdf(n, xi) - ti) / slope;
+                xi = (xi - step).clamp(0.0, PI);
+                if step.abs() < tolerance {
+                    break;
+                }
+            }
+        }
+        xi
+    }
+}
+
+/// Evaluates the integral of `sin^k` from the recursion used for the `tp` tables,
+/// `T_k = ((k - 1) T_(k-2) - cos(t) sin^(k-1)(t)) / k` with `T_0 = t` and `T_1 = -cos(t)`.
+/// The integral over `[a, b]` is `T_k(b) - T_k(a)`.
+pub(crate) fn sine_power_cdf(k: usize, t: f64) -> f64 {
+    let (sin_t, cos_t) = t.sin_cos();
+    let (mut res, mut j) = if k.is_multiple_of(2) {
+        (t, 0)
+    } else {
+        (-cos_t, 1)
+    };
+    while j < k {
+        j += 2;
+        res = ((j - 1) as f64 * res - cos_t * sin_t.powi(j as i32 - 1)) / j as f64;
+    }
+    res
+}
+
 enum SphereVariant {
     ForS3(Box<Sphere3>),
     ForSn(Box<SphereN>),
@@ -153,7 +241,12 @@ enum SphereVariant {
 pub struct SphereN {
     vdc: VdCorput,
     s_gen: SphereVariant,
+    x: Array1<f64>,
     tp: Array1<f64>,
+    inversion: Inversion,
+    n: usize,
+    base: Vec<usize>,
+    index: usize,
 }
 
 impl SphereN {
@@ -170,28 +263,76 @@ impl SphereN {
     ///
     /// A new `SphereN` object is being returned from the `new` function.
     pub fn new(n: usize, base: &[usize]) -> Self {
+        Self::with_options(n, base, GRID_SIZE, Inversion::default())
+    }
+
+    /// Like `new`, but with `grid_size` nodes in the interpolation tables of every level,
+    /// down to the innermost `Sphere3`, and the given inversion of the polar-angle CDFs. See `SphereBuilder` for a
+    /// validated way to set these options.
+    pub(crate) fn with_options(
+        n: usize,
+        base: &[usize],
+        grid_size: usize,
+        inversion: Inversion,
+    ) -> Self {
         assert!(n >= 3);
+        assert!(grid_size >= 2);
         let (s_gen, tp_minus2) = if n == 3 {
-            let s_gen = SphereVariant::ForS3(Box::new(Sphere3::new(&base[1..4])));
+            let s_gen = SphereVariant::ForS3(Box::new(Sphere3::with_options(
+                &base[1..4],
+                grid_size,
+                inversion,
+            )));
             (s_gen, GL.neg_cosine.clone())
         } else {
-            let s_minus1 = SphereN::new(n - 1, &base[1..]);
+            let s_minus1 = SphereN::with_options(n - 1, &base[1..], grid_size, inversion);
             let ssn_minus2 = s_minus1.get_tp_minus1().clone();
             let s_gen = SphereVariant::ForSn(Box::new(s_minus1));
             (s_gen, ssn_minus2)
         };
 
-        let tp = (((n - 1) as f64) * tp_minus2
-            + &GL.neg_cosine * &GL.sine.mapv(|x| x.powi((n - 1) as i32)))
-            / n as f64;
+        let (x, tp) = if grid_size == GRID_SIZE {
+            let tp = (((n - 1) as f64) * tp_minus2
+                + &GL.neg_cosine * &GL.sine.mapv(|x| x.powi((n - 1) as i32)))
+                / n as f64;
+            (X.clone(), tp)
+        } else {
+            let x = Array1::linspace(0.0, PI, grid_size);
+            let tp = x.mapv(|t| sine_power_cdf(n, t));
+            (x, tp)
+        };
 
         SphereN {
             vdc: VdCorput::new(base[0]),
             s_gen,
+            x,
             tp,
+            inversion,
+            n,
+            base: base[..=n].to_vec(),
+            index: 0,
         }
     }
 
+    /// Returns the position in the sequence: the seed of the last `reseed` plus the number
+    /// of points generated since. Reseeding a new generator with it continues the stream.
+    #[inline]
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the dimension `n` the generator was constructed with.
+    #[inline]
+    pub fn get_n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the bases of the underlying sequences.
+    #[inline]
+    pub fn get_base(&self) -> &[usize] {
+        &self.base
+    }
+
     /// Returns a reference to the precomputed interpolation points.
     #[inline]
     pub fn get_tp(&self) -> &Array1<f64> {
@@ -207,14 +348,33 @@ impl SphereN {
         }
     }
 
+    /// Returns the number of nodes of the interpolation tables.
+    #[inline]
+    pub fn get_grid_size(&self) -> usize {
+        self.x.len()
+    }
+
+    /// Returns the inversion method of the polar-angle CDF.
+    #[inline]
+    pub fn get_inversion(&self) -> Inversion {
+        self.inversion
+    }
+
     /// Generates the next point on the n-dimensional sphere as a vector.
     ///
     /// Returns a vector of length n+1 representing a point on the n-dimensional sphere.
     #[inline]
     pub fn pop_vec(&mut self) -> Vec<f64> {
+        self.index += 1;
         let vd = self.vdc.pop();
         let ti = self.tp[0] + (self.tp[self.tp.len() - 1] - self.tp[0]) * vd; // map to [t0, tm-1];
-        let xi = interp(&self.tp.to_vec(), &X.to_vec(), ti, &InterpMode::default());
+        let xi = interp(
+            &self.tp.to_vec(),
+            &self.x.to_vec(),
+            ti,
+            &InterpMode::default(),
+        );
+        let xi = self.inversion.refine(self.n, xi, ti);
         let sinphi = xi.sin();
         let mut res = match &mut self.s_gen {
             SphereVariant::ForS3(gen_3) => gen_3.pop().to_vec(),
@@ -230,6 +390,7 @@ impl SphereN {
     /// Reseeds the random number generators with a new seed.
     #[inline]
     pub fn reseed(&mut self, seed: usize) {
+        self.index = seed;
         self.vdc.reseed(seed);
         match &mut self.s_gen {
             SphereVariant::ForS3(gen_3) => gen_3.reseed(seed),
@@ -237,3 +398,71 @@ impl SphereN {
         }
     }
 }
+
+/// Lets `SphereN` be used wherever a `CylindGen` point source is expected.
+impl CylindGen for SphereN {
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        SphereN::pop_vec(self)
+    }
+
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        SphereN::reseed(self, seed)
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.n + 2
+    }
+
+    fn checkpoint(&self) -> Option<crate::state::GenState> {
+        Some(self.state())
+    }
+}
+
+/// Adapts `Sphere3` to `CylindGen` without clashing with `SphereGen::reseed`.
+struct Sphere3Gen(Sphere3);
+
+impl CylindGen for Sphere3Gen {
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.0.pop().to_vec()
+    }
+
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.0.reseed(seed)
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        4
+    }
+}
+
+/// Creates a generator of uniformly distributed low-discrepancy points on `S^n` using the
+/// recursive sphere method.
+///
+/// Unlike `SphereN::new`, `n` is the dimension of the sphere itself: the points live in
+/// `R^(n+1)`. `S^1` uses `Circle`, `S^2` uses `Sphere`, `S^3` uses `Sphere3` and higher
+/// dimensions use `SphereN::new(n - 1, ..)`. In every case `n` bases are consumed.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::sphere_n::sphere_gen;
+///
+/// let mut sgen = sphere_gen(3, &[2, 3, 5]);
+/// sgen.reseed(0);
+/// assert_eq!(sgen.pop_vec().len(), 4);
+/// ```
+pub fn sphere_gen(n: usize, base: &[usize]) -> Box<dyn CylindGen> {
+    match n {
+        0 => panic!("the sphere dimension must be at least 1"),
+        1 => Box::new(Circle::new(base[0])),
+        2 => Box::new(Sphere::new(&base[0..2])),
+        3 => Box::new(Sphere3Gen(Sphere3::new(&base[0..3]))),
+        _ => Box::new(SphereN::new(n - 1, base)),
+    }
+}
@@ -0,0 +1,283 @@
+//! Checkpointing of generator configurations and positions, for saving and resuming streams.
+
+use crate::builder::scrambled;
+use crate::cylind_n::{CylindGen, CylindN};
+use crate::sphere_n::{Inversion, SphereN, GRID_SIZE};
+
+/// The construction of a checkpointed generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GenMethod {
+    /// `SphereN`, the recursive sphere method.
+    SphereN,
+    /// `CylindN`, the cylindrical coordinate method.
+    CylindN,
+}
+
+/// The configuration and position of a `SphereN` or `CylindN` generator.
+///
+/// The position `index` is the seed of the last `reseed` plus the number of points generated
+/// since, so it also records any seed offset. A generator rebuilt from the state continues
+/// the exact same stream. With the `serde` feature, the state (and the generators
+/// themselves, through it) can be serialized to checkpoint long-running jobs.
+///
+/// Generators scrambled by `SphereBuilder::scramble` or `CylindBuilder::scramble` are
+/// checkpointed through `CylindGen::checkpoint`, which records the `scramble` seed; `build`
+/// reapplies the same rotation.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::state::GenState;
+/// use sphere_n_rs::SphereN;
+///
+/// let mut sgen = SphereN::new(3, &[2, 3, 5, 7]);
+/// sgen.reseed(5);
+/// sgen.pop_vec();
+/// let state = sgen.state();
+/// assert_eq!(state.index, 6);
+/// let mut restored = SphereN::from_state(&state).unwrap();
+/// assert_eq!(restored.pop_vec(), sgen.pop_vec());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenState {
+    /// The construction method.
+    pub method: GenMethod,
+    /// The dimension `n` passed to `SphereN::new` or `CylindN::new`.
+    pub n: usize,
+    /// The bases of the underlying sequences, `n + 1` of them for `SphereN` and `n` for
+    /// `CylindN`.
+    pub base: Vec<usize>,
+    /// The position in the sequence.
+    pub index: usize,
+    /// The number of nodes of the interpolation tables (`SphereN` only).
+    #[cfg_attr(feature = "serde", serde(default = "default_grid_size"))]
+    pub grid_size: usize,
+    /// The inversion of the polar-angle CDF (`SphereN` only).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inversion: Inversion,
+    /// The seed of the random rotation applied to every point, if the generator is
+    /// scrambled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scramble: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+fn default_grid_size() -> usize {
+    GRID_SIZE
+}
+
+impl GenState {
+    /// Checks the state before a generator is built from it.
+    fn validate(&self, method: GenMethod) -> Result<(), String> {
+        if self.method != method {
+            return Err(format!(
+                "expected a {:?} state, found {:?}",
+                method, self.method
+            ));
+        }
+        let (min_n, nbases) = match method {
+            GenMethod::SphereN => (3, self.n + 1),
+            GenMethod::CylindN => (2, self.n),
+        };
+        if self.n < min_n {
+            return Err(format!(
+                "{:?} needs n >= {}, found {}",
+                method, min_n, self.n
+            ));
+        }
+        if self.base.len() != nbases {
+            return Err(format!(
+                "expected {} bases, found {}",
+                nbases,
+                self.base.len()
+            ));
+        }
+        if self.scramble.is_some() {
+            return Err(format!(
+                "a scrambled generator is not a {:?}, use `GenState::build`",
+                method
+            ));
+        }
+        if self.base.iter().any(|&b| b < 2) {
+            return Err("the bases must be at least 2".to_string());
+        }
+        if self.grid_size < 2 {
+            return Err("the grid needs at least 2 nodes".to_string());
+        }
+        self.inversion.validate()?;
+        Ok(())
+    }
+
+    /// Builds the generator described by the state, at its position, scrambled by the same
+    /// rotation if `scramble` is set.
+    pub fn build(&self) -> Result<Box<dyn CylindGen>, String> {
+        let plain = GenState {
+            scramble: None,
+            ..self.clone()
+        };
+        let (res, dim): (Box<dyn CylindGen>, usize) = match self.method {
+            GenMethod::SphereN => (Box::new(SphereN::from_state(&plain)?), self.n + 2),
+            GenMethod::CylindN => (Box::new(CylindN::from_state(&plain)?), self.n + 1),
+        };
+        Ok(match self.scramble {
+            Some(seed) => scrambled(res, seed, dim),
+            None => res,
+        })
+    }
+}
+
+impl SphereN {
+    /// Returns the configuration and position of the generator.
+    pub fn state(&self) -> GenState {
+        GenState {
+            method: GenMethod::SphereN,
+            n: self.get_n(),
+            base: self.get_base().to_vec(),
+            index: self.get_index(),
+            grid_size: self.get_grid_size(),
+            inversion: self.get_inversion(),
+            scramble: None,
+        }
+    }
+
+    /// Rebuilds a generator from its state, continuing the same stream.
+    pub fn from_state(state: &GenState) -> Result<Self, String> {
+        state.validate(GenMethod::SphereN)?;
+        let mut res = SphereN::with_options(state.n, &state.base, state.grid_size, state.inversion);
+        res.reseed(state.index);
+        Ok(res)
+    }
+}
+
+impl CylindN {
+    /// Returns the configuration and position of the generator.
+    pub fn state(&self) -> GenState {
+        GenState {
+            method: GenMethod::CylindN,
+            n: self.get_n(),
+            base: self.get_base().to_vec(),
+            index: self.get_index(),
+            grid_size: GRID_SIZE,
+            inversion: Inversion::default(),
+            scramble: None,
+        }
+    }
+
+    /// Rebuilds a generator from its state, continuing the same stream.
+    pub fn from_state(state: &GenState) -> Result<Self, String> {
+        state.validate(GenMethod::CylindN)?;
+        let mut res = CylindN::new(state.n, &state.base);
+        res.reseed(state.index);
+        Ok(res)
+    }
+}
+
+/// Serializes the generator as its `GenState`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SphereN {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.state().serialize(serializer)
+    }
+}
+
+/// Deserializes the generator from its `GenState`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SphereN {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = GenState::deserialize(deserializer)?;
+        SphereN::from_state(&state).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes the generator as its `GenState`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CylindN {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.state().serialize(serializer)
+    }
+}
+
+/// Deserializes the generator from its `GenState`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CylindN {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = GenState::deserialize(deserializer)?;
+        CylindN::from_state(&state).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{CylindBuilder, SphereBuilder};
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_state_resumes_stream() {
+        let mut cgen = CylindN::new(4, &PRIME_TABLE);
+        cgen.reseed(100);
+        cgen.pop_batch(37);
+        let state = cgen.state();
+        assert_eq!(state.index, 137);
+        let mut restored = state.build().unwrap();
+        assert_eq!(restored.pop_batch(20), cgen.pop_batch(20));
+
+        let bad = GenState {
+            base: vec![2, 3],
+            ..state.clone()
+        };
+        assert!(CylindN::from_state(&bad).is_err());
+        assert!(SphereN::from_state(&state).is_err());
+    }
+
+    #[test]
+    fn test_scrambled_state_resumes_stream() {
+        let mut sgen = SphereBuilder::new(5)
+            .scramble(17)
+            .start_index(4)
+            .build()
+            .unwrap();
+        sgen.pop_batch(9);
+        let state = sgen.checkpoint().unwrap();
+        assert_eq!((state.index, state.scramble), (13, Some(17)));
+        let mut restored = state.build().unwrap();
+        assert_eq!(restored.pop_batch(10), sgen.pop_batch(10));
+        // the plain generator of a scrambled state would give different points
+        assert!(SphereN::from_state(&state).is_err());
+
+        let mut cgen = CylindBuilder::new(3).scramble(5).build().unwrap();
+        cgen.pop_batch(3);
+        let mut restored = cgen.checkpoint().unwrap().build().unwrap();
+        assert_eq!(restored.pop_vec(), cgen.pop_vec());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut sgen = SphereN::new(4, &PRIME_TABLE);
+        sgen.reseed(3);
+        sgen.pop_batch(10);
+        let json = serde_json::to_string(&sgen).unwrap();
+        assert_eq!(
+            json,
+            r#"{"method":"sphere_n","n":4,"base":[2,3,5,7,11],"index":13,"grid_size":300,"inversion":"interpolate","scramble":null}"#
+        );
+        let mut restored: SphereN = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.pop_batch(10), sgen.pop_batch(10));
+
+        let mut scrambled = CylindBuilder::new(4).scramble(3).build().unwrap();
+        scrambled.pop_batch(5);
+        let json = serde_json::to_string(&scrambled.checkpoint().unwrap()).unwrap();
+        assert!(json.ends_with(r#""scramble":3}"#));
+        let state: GenState = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.build().unwrap().pop_vec(), scrambled.pop_vec());
+        let bad = r#"{"method":"sphere_n","n":4,"base":[2,3,5,7],"index":0}"#;
+        assert!(serde_json::from_str::<SphereN>(bad).is_err());
+        // checkpoints without table options get the defaults
+        let old = r#"{"method":"cylind_n","n":2,"base":[2,3],"index":4}"#;
+        assert_eq!(serde_json::from_str::<CylindN>(old).unwrap().get_index(), 4);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::sphere_gen;
+use ndarray::{s, Array1, Array2};
+
+/// Generate low-discrepancy orthonormal k-frames in `R^n` (the Stiefel manifold `V_k(R^n)`).
+///
+/// Column `j` of a frame is a point of `S^(n-1-j)` from its own uniform sphere generator,
+/// expressed in an orthonormal basis of the complement of the previous columns. After each
+/// column the complement basis is updated with the Householder reflection that sends the new
+/// direction to the last axis, so the frame is orthonormal by construction. Since each
+/// column is uniform on the sphere of its complement, the frames are distributed according
+/// to the invariant (Haar) measure.
+///
+/// The sphere generators consume consecutive bases: `n - 1` for the first column, `n - 2`
+/// for the second, and so on.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::stiefel::Stiefel;
+/// use sphere_n_rs::PRIME_TABLE;
+/// use approx_eq::assert_approx_eq;
+///
+/// let mut sgen = Stiefel::new(4, 2, &PRIME_TABLE);
+/// sgen.reseed(0);
+/// let frame = sgen.pop();
+/// assert_eq!(frame.dim(), (4, 2));
+/// assert_approx_eq!(frame.column(0).dot(&frame.column(0)), 1.0);
+/// assert!(frame.column(0).dot(&frame.column(1)).abs() < 1e-12);
+/// ```
+pub struct Stiefel {
+    n: usize,
+    s_gens: Vec<Box<dyn CylindGen>>,
+}
+
+impl Stiefel {
+    /// The function `new` constructs a new `Stiefel` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `n`: The dimension of the ambient space.
+    /// * `k`: The number of columns of the frames, `1 <= k < n`.
+    /// * `base`: The bases of the underlying sequences; `k * (2n - k - 1) / 2` of them are
+    ///   consumed.
+    ///
+    /// Returns:
+    ///
+    /// A new `Stiefel` object is being returned from the `new` function.
+    pub fn new(n: usize, k: usize, base: &[usize]) -> Self {
+        assert!(k >= 1 && k < n);
+        let mut s_gens = Vec::with_capacity(k);
+        let mut offset = 0;
+        for j in 0..k {
+            let m = n - 1 - j;
+            s_gens.push(sphere_gen(m, &base[offset..offset + m]));
+            offset += m;
+        }
+        Stiefel { n, s_gens }
+    }
+
+    /// Generates the next frame as an `n x k` matrix with orthonormal columns.
+    pub fn pop(&mut self) -> Array2<f64> {
+        let n = self.n;
+        let mut res = Array2::zeros((n, self.s_gens.len()));
+        // orthonormal basis of the complement of the columns so far, one vector per column
+        let mut complement = Array2::<f64>::eye(n);
+        for (j, s_gen) in self.s_gens.iter_mut().enumerate() {
+            let m = n - j;
+            let u = Array1::from(s_gen.pop_vec());
+            res.column_mut(j).assign(&complement.dot(&u));
+            // reflection H = I - 2 v v^T / (v . v) with v = u - e_m maps u to e_m, so the
+            // first m - 1 columns of H span the complement of u
+            let mut v = u;
+            v[m - 1] -= 1.0;
+            let vv = v.dot(&v);
+            let mut basis = complement.slice(s![.., ..m - 1]).to_owned();
+            if vv > 1e-24 {
+                let cv = complement.dot(&v);
+                for (c, mut col) in basis.columns_mut().into_iter().enumerate() {
+                    col.scaled_add(-2.0 * v[c] / vv, &cv);
+                }
+            }
+            complement = basis;
+        }
+        res
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    pub fn reseed(&mut self, seed: usize) {
+        for s_gen in self.s_gens.iter_mut() {
+            s_gen.reseed(seed);
+        }
+    }
+}
+
+/// Generate low-discrepancy points on the Grassmann manifold of k-dimensional subspaces of
+/// `R^n`, represented by their orthogonal projection matrices `F F^T` for frames `F` from a
+/// `Stiefel` generator.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::stiefel::Grassmann;
+/// use sphere_n_rs::PRIME_TABLE;
+/// use approx_eq::assert_approx_eq;
+///
+/// let mut ggen = Grassmann::new(3, 1, &PRIME_TABLE);
+/// ggen.reseed(0);
+/// let proj = ggen.pop();
+/// assert_approx_eq!(proj.diag().sum(), 1.0);
+/// ```
+pub struct Grassmann {
+    stiefel: Stiefel,
+}
+
+impl Grassmann {
+    /// The function `new` constructs a new `Grassmann` object with specified parameters, with
+    /// the same arguments as `Stiefel::new`.
+    pub fn new(n: usize, k: usize, base: &[usize]) -> Self {
+        Grassmann {
+            stiefel: Stiefel::new(n, k, base),
+        }
+    }
+
+    /// Generates the next subspace as its `n x n` orthogonal projection matrix.
+    #[inline]
+    pub fn pop(&mut self) -> Array2<f64> {
+        let frame = self.stiefel.pop();
+        frame.dot(&frame.t())
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    pub fn reseed(&mut self, seed: usize) {
+        self.stiefel.reseed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_stiefel_orthonormal() {
+        for (n, k) in [(2, 1), (3, 2), (5, 3), (6, 5)] {
+            let mut sgen = Stiefel::new(n, k, &PRIME_TABLE);
+            for _ in 0..50 {
+                let frame = sgen.pop();
+                let gram = frame.t().dot(&frame);
+                for ((i, j), g) in gram.indexed_iter() {
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert!((g - expected).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_grassmann_mean_projection() {
+        // the mean projection over the invariant measure is (k / n) I
+        let (n, k, npoints) = (4, 2, 2000);
+        let mut ggen = Grassmann::new(n, k, &PRIME_TABLE);
+        let mut mean = Array2::<f64>::zeros((n, n));
+        for _ in 0..npoints {
+            let proj = ggen.pop();
+            assert!((&proj.dot(&proj) - &proj).iter().all(|x| x.abs() < 1e-12));
+            mean += &(proj / npoints as f64);
+        }
+        for ((i, j), m) in mean.indexed_iter() {
+            let expected = if i == j { k as f64 / n as f64 } else { 0.0 };
+            assert!((m - expected).abs() < 1e-2, "{:?}", mean);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::cylind_n::CylindGen;
+use crate::zonal::ZonalGen;
+use std::f64::consts::PI;
+
+/// Generate low-discrepancy samples of the von Mises-Fisher distribution on `S^n`.
+///
+/// The density is proportional to `exp(kappa * mu . x)`, a zonal density around `mu`: the
+/// samples come from a `ZonalGen` with the profile `exp(kappa * (t - 1))`, whose CDF table
+/// is restricted to the polar angles where the density is not negligible.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::vmf::VonMisesFisher;
+/// use sphere_n_rs::CylindGen;
+///
+/// let mut vgen = VonMisesFisher::new(2, 50.0, &[1.0, 0.0, 0.0], &[2, 3]);
+/// vgen.reseed(0);
+/// let res = vgen.pop_vec();
+/// assert!(res[0] > 0.8);
+/// ```
+pub struct VonMisesFisher {
+    zonal: ZonalGen,
+}
+
+impl VonMisesFisher {
+    /// The function `new` constructs a new `VonMisesFisher` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `n`: The dimension of the sphere; the points live in `R^(n+1)`.
+    /// * `kappa`: The concentration parameter (`kappa >= 0`, zero being uniform).
+    /// * `mu`: The mean direction, a vector of length `n + 1` (normalized internally).
+    /// * `base`: The bases of the underlying sequences; `n` of them are consumed, the first
+    ///   for the axial component and the rest for the sub-sphere `S^(n-1)`.
+    ///
+    /// Returns:
+    ///
+    /// A new `VonMisesFisher` object is being returned from the `new` function.
+    pub fn new(n: usize, kappa: f64, mu: &[f64], base: &[usize]) -> Self {
+        assert!(kappa >= 0.0);
+        let max_angle = if kappa > 0.0 {
+            PI.min(((n as f64).sqrt() + 10.0) / kappa.sqrt())
+        } else {
+            PI
+        };
+        let density = move |t: f64| (kappa * (t - 1.0)).exp();
+        VonMisesFisher {
+            zonal: ZonalGen::with_max_angle(n, density, max_angle, base).with_pole(mu),
+        }
+    }
+}
+
+impl CylindGen for VonMisesFisher {
+    /// Generates the next sample of the distribution.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        self.zonal.pop_vec()
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.zonal.reseed(seed);
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.zonal.dim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere_n::sphere_gen;
+    use approx_eq::assert_approx_eq;
+    use lds_rs::lds::{VdCorput, PRIME_TABLE};
+
+    /// The mean resultant length `E[mu . x]` of the vMF distribution on `S^n`,
+    /// `I_{(n+1)/2}(kappa) / I_{(n-1)/2}(kappa)`, from the power series of the Bessel
+    /// functions.
+    fn mean_resultant(n: usize, kappa: f64) -> f64 {
+        let bessel_i = |nu: f64| -> f64 {
+            let mut term = (kappa / 2.0).powf(nu) / gamma(nu + 1.0);
+            let mut sum = term;
+            for k in 1..200 {
+                let k = k as f64;
+                term *= (kappa / 2.0).powi(2) / (k * (k + nu));
+                sum += term;
+            }
+            sum
+        };
+        bessel_i((n + 1) as f64 / 2.0) / bessel_i((n - 1) as f64 / 2.0)
+    }
+
+    /// Gamma function at positive integers and half-integers.
+    fn gamma(x: f64) -> f64 {
+        let mut res = if x.fract() == 0.0 { 1.0 } else { PI.sqrt() };
+        let mut t = x - 1.0;
+        while t > 0.0 {
+            res *= t;
+            t -= 1.0;
+        }
+        res
+    }
+
+    #[test]
+    fn test_vmf_mean_direction() {
+        for (n, kappa) in [(1, 2.0), (2, 5.0), (3, 10.0), (5, 1.0), (4, 200.0)] {
+            let mut mu: Vec<f64> = (0..=n).map(|i| (i + 1) as f64).collect();
+            let norm = mu.iter().map(|x| x * x).sum::<f64>().sqrt();
+            mu.iter_mut().for_each(|x| *x /= norm);
+            let mut vgen = VonMisesFisher::new(n, kappa, &mu, &PRIME_TABLE);
+            let npoints = 4000;
+            let mut mean = vec![0.0; n + 1];
+            for _ in 0..npoints {
+                let pt = vgen.pop_vec();
+                assert_approx_eq!(pt.iter().map(|x| x * x).sum::<f64>(), 1.0);
+                for (m, x) in mean.iter_mut().zip(&pt) {
+                    *m += x / npoints as f64;
+                }
+            }
+            // the mean of the samples is the mean resultant length times mu
+            let expected = mean_resultant(n, kappa);
+            for (m, u) in mean.iter().zip(&mu) {
+                assert!((m - expected * u).abs() < 5e-3, "n = {}: {:?}", n, mean);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vmf_s2_closed_form() {
+        // on S^2 the axial component has the closed-form inverse CDF
+        // t = 1 + ln(v + (1 - v) exp(-2 kappa)) / kappa, where v = 1 - u since the table
+        // starts from the pole
+        let kappa = 3.0;
+        let mut vgen = VonMisesFisher::new(2, kappa, &[0.0, 0.0, 1.0], &[2, 3]);
+        let mut vdc = VdCorput::new(2);
+        for _ in 0..100 {
+            let v = 1.0 - vdc.pop();
+            let t = 1.0 + (v + (1.0 - v) * (-2.0 * kappa).exp()).ln() / kappa;
+            assert!((vgen.pop_vec()[2] - t).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_vmf_zero_kappa_is_uniform() {
+        let mut vgen = VonMisesFisher::new(4, 0.0, &[0.0, 0.0, 0.0, 0.0, 1.0], &PRIME_TABLE);
+        let mut sgen = sphere_gen(4, &PRIME_TABLE);
+        let (a, b) = (vgen.pop_batch(2000), sgen.pop_batch(2000));
+        // same axial sequence as the recursive sphere method, up to the table resolution
+        for (x, y) in a.column(4).iter().zip(b.column(4).iter()) {
+            assert!((x - y).abs() < 1e-4);
+        }
+    }
+}
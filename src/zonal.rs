@@ -0,0 +1,231 @@
+use crate::cylind_n::CylindGen;
+use crate::sphere_n::sphere_gen;
+use interp::{interp, InterpMode};
+use lds_rs::lds::VdCorput;
+use std::f64::consts::PI;
+
+/// The number of nodes of the tabulated polar-angle CDF.
+pub(crate) const TABLE_SIZE: usize = 1024;
+
+/// Generate low-discrepancy samples of a rotationally symmetric (zonal) distribution on
+/// `S^n`.
+///
+/// The distribution has density `f(cos(theta))` with respect to the surface measure, where
+/// `theta` is the angle to the pole. As `SphereN` does with its `tp` table, the CDF of the
+/// polar angle, proportional to the integral of `f(cos(theta)) sin^(n-1)(theta)`, is
+/// tabulated once on a grid over `[0, pi]` and inverted by interpolation; a `VdCorput`
+/// sequence drives the polar angle and a uniform generator of `S^(n-1)` the remaining
+/// coordinates.
+///
+/// The pole is the last axis unless another one is set with `with_pole`.
+///
+/// # Examples
+///
+/// ```
+/// use sphere_n_rs::zonal::ZonalGen;
+/// use sphere_n_rs::CylindGen;
+///
+/// // points on S^2 concentrated around the north pole
+/// let mut zgen = ZonalGen::new(2, |t| (1.0 + t).powi(4), &[2, 3]);
+/// zgen.reseed(0);
+/// let pts = zgen.pop_batch(1000);
+/// let mean_z = pts.column(2).mean().unwrap();
+/// assert!((mean_z - 2.0 / 3.0).abs() < 1e-2);
+/// ```
+pub struct ZonalGen {
+    n: usize,
+    vdc: VdCorput,
+    /// Uniform generator of the sub-sphere `S^(n-1)`, absent on the circle.
+    s_gen: Option<Box<dyn CylindGen>>,
+    cdf: Vec<f64>,
+    theta: Vec<f64>,
+    /// The Householder vector mapping the last axis onto the pole, if they differ.
+    householder: Option<Vec<f64>>,
+}
+
+impl ZonalGen {
+    /// The function `new` constructs a new `ZonalGen` object with specified parameters.
+    ///
+    /// Arguments:
+    ///
+    /// * `n`: The dimension of the sphere; the points live in `R^(n+1)`.
+    /// * `density`: The zonal density profile `f(t)` for `t = cos(theta)` in `[-1, 1]`. It
+    ///   must be non-negative and need not be normalized.
+    /// * `base`: The bases of the underlying sequences; `n` of them are consumed, the first
+    ///   for the polar angle and the rest for the sub-sphere `S^(n-1)`.
+    ///
+    /// Returns:
+    ///
+    /// A new `ZonalGen` object is being returned from the `new` function.
+    pub fn new<F: Fn(f64) -> f64>(n: usize, density: F, base: &[usize]) -> Self {
+        Self::with_max_angle(n, density, PI, base)
+    }
+
+    /// Like `new`, but tabulates the CDF on `[0, max_angle]` only, for densities that are
+    /// negligible further away from the pole.
+    pub(crate) fn with_max_angle<F: Fn(f64) -> f64>(
+        n: usize,
+        density: F,
+        max_angle: f64,
+        base: &[usize],
+    ) -> Self {
+        assert!(n >= 1);
+        assert!(max_angle > 0.0 && max_angle <= PI);
+        // On S^1 the polar angle covers [-max_angle, max_angle] and no sub-sphere is needed.
+        let lo = if n == 1 { -max_angle } else { 0.0 };
+        let step = (max_angle - lo) / (TABLE_SIZE - 1) as f64;
+        let theta: Vec<f64> = (0..TABLE_SIZE).map(|i| lo + step * i as f64).collect();
+        let weights: Vec<f64> = theta
+            .iter()
+            .map(|&t| density(t.cos()) * t.sin().abs().powi(n as i32 - 1))
+            .collect();
+        let cdf = tabulate_cdf(&weights, step);
+
+        ZonalGen {
+            n,
+            vdc: VdCorput::new(base[0]),
+            s_gen: if n == 1 {
+                None
+            } else {
+                Some(sphere_gen(n - 1, &base[1..]))
+            },
+            cdf,
+            theta,
+            householder: None,
+        }
+    }
+
+    /// Sets the pole of the distribution to the direction `pole` (normalized internally),
+    /// a vector of length `n + 1`.
+    pub fn with_pole(mut self, pole: &[f64]) -> Self {
+        assert_eq!(pole.len(), self.n + 1);
+        let norm = pole.iter().map(|x| x * x).sum::<f64>().sqrt();
+        assert!(norm > 0.0);
+        let mut axis: Vec<f64> = pole.iter().map(|x| x / norm).collect();
+        let last = axis.len() - 1;
+        axis[last] -= 1.0;
+        self.householder = if axis.iter().map(|x| x * x).sum::<f64>() > 1e-24 {
+            Some(axis)
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Returns the tabulated CDF of the polar angle, on the grid given by `get_angles`.
+    #[inline]
+    pub fn get_cdf(&self) -> &[f64] {
+        &self.cdf
+    }
+
+    /// Returns the grid of polar angles of the tabulated CDF.
+    #[inline]
+    pub fn get_angles(&self) -> &[f64] {
+        &self.theta
+    }
+}
+
+/// Returns the normalized CDF of the density sampled as `weights` on a uniform grid of
+/// spacing `step`, integrated with the trapezoidal rule.
+pub(crate) fn tabulate_cdf(weights: &[f64], step: f64) -> Vec<f64> {
+    assert!(
+        weights.iter().all(|&w| w >= 0.0),
+        "the density must be non-negative"
+    );
+    let mut cdf = vec![0.0; weights.len()];
+    for i in 1..weights.len() {
+        cdf[i] = cdf[i - 1] + 0.5 * (weights[i - 1] + weights[i]) * step;
+    }
+    let total = cdf[weights.len() - 1];
+    assert!(
+        total > 0.0 && total.is_finite(),
+        "the density must have a positive, finite integral"
+    );
+    cdf.iter_mut().for_each(|c| *c /= total);
+    cdf
+}
+
+impl CylindGen for ZonalGen {
+    /// Generates the next sample of the distribution.
+    #[inline]
+    fn pop_vec(&mut self) -> Vec<f64> {
+        let vd = self.vdc.pop();
+        let theta = interp(&self.cdf, &self.theta, vd, &InterpMode::default());
+        let (sin_t, cos_t) = theta.sin_cos();
+        let mut res = match &mut self.s_gen {
+            Some(s_gen) => {
+                let mut res = s_gen.pop_vec();
+                for xi in res.iter_mut() {
+                    *xi *= sin_t;
+                }
+                res
+            }
+            None => vec![sin_t],
+        };
+        res.push(cos_t);
+        if let Some(v) = &self.householder {
+            let scale = 2.0 * res.iter().zip(v).map(|(x, y)| x * y).sum::<f64>()
+                / v.iter().map(|y| y * y).sum::<f64>();
+            for (xi, vi) in res.iter_mut().zip(v) {
+                *xi -= scale * vi;
+            }
+        }
+        res
+    }
+
+    /// Reseeds the underlying generators with the given seed.
+    #[inline]
+    fn reseed(&mut self, seed: usize) {
+        self.vdc.reseed(seed);
+        if let Some(s_gen) = &mut self.s_gen {
+            s_gen.reseed(seed);
+        }
+    }
+
+    #[inline]
+    fn dim(&self) -> usize {
+        self.n + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lds_rs::lds::PRIME_TABLE;
+
+    #[test]
+    fn test_zonal_uniform_matches_sphere_n() {
+        let mut zgen = ZonalGen::new(4, |_| 1.0, &PRIME_TABLE);
+        let mut sgen = sphere_gen(4, &PRIME_TABLE);
+        let (a, b) = (zgen.pop_batch(2000), sgen.pop_batch(2000));
+        // same axial sequence as the recursive sphere method, up to the table resolution
+        for (x, y) in a.column(4).iter().zip(b.column(4).iter()) {
+            assert!((x - y).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_zonal_axial_cdf() {
+        // on S^2 the density (1 + t) / 2 gives P(t <= s) = (1 + s)^2 / 4
+        let mut zgen = ZonalGen::new(2, |t| 1.0 + t, &PRIME_TABLE);
+        let npoints = 4000;
+        let pts = zgen.pop_batch(npoints);
+        for s in [-0.8, -0.3, 0.0, 0.4, 0.9] {
+            let count = pts.column(2).iter().filter(|&&t| t <= s).count();
+            let expected = (1.0 + s) * (1.0 + s) / 4.0;
+            assert!((count as f64 / npoints as f64 - expected).abs() < 2e-3);
+        }
+    }
+
+    #[test]
+    fn test_zonal_pole() {
+        // a density supported on the upper hemisphere, moved to the first axis on S^3
+        let mut zgen =
+            ZonalGen::new(3, |t| t.max(0.0), &PRIME_TABLE).with_pole(&[1.0, 0.0, 0.0, 0.0]);
+        for _ in 0..500 {
+            let pt = zgen.pop_vec();
+            assert!(pt[0] >= -1e-9);
+            assert!((pt.iter().map(|x| x * x).sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+    }
+}